@@ -1,50 +1,95 @@
-use std::collections::HashMap;
-use std::fs;
+mod sorted;
+
+use crate::sorted::{compare, radix_sort, read_columns};
+use libparse::{ParseResult, Source};
+use std::fs::File;
+use std::io::BufReader;
+
+type Columns = (Vec<u64>, Vec<u64>);
 
 fn main() {
-    part1();
-    part2();
+    let args = runner::day_args();
+    match args.as_slice() {
+        [] => runner::run("d01", read_pairs, part1, part2),
+        [flag, path] if flag == "--stream" => stream(path),
+        _ => runner::bad_day_args(
+            "d01",
+            "d01 takes --stream FILE, a separate mode that reads FILE itself and just prints the answers, \
+             with no answer checking, --json, or --bench",
+        ),
+    }
 }
 
-fn part1() {
-    let (col1, col2) = read_pairs();
+/// For inputs too big to read in as text: the file goes by a line at a time, and both answers
+/// come out of one pass over the sorted columns. The runner wants the whole input as a string,
+/// so this is its own mode instead: nothing gets checked against the answers file, and the
+/// runner's flags like `--json` and `--bench` don't apply.
+fn stream(path: &str) {
+    let file = File::open(path).unwrap_or_else(|err| {
+        eprintln!("d01: couldn't read {path}: {err}");
+        std::process::exit(1);
+    });
+    let (mut col1, mut col2) = read_columns(BufReader::new(file)).unwrap_or_else(|err| {
+        eprintln!("d01: {path}: {err}");
+        std::process::exit(1);
+    });
+    radix_sort(&mut col1);
+    radix_sort(&mut col2);
 
-    let sum_diffs: u64 = col1.iter().zip(col2.iter())
-        .map(|(l, r)| l.abs_diff(*r))
-        .sum();
+    let (distance, similarity) = compare(&col1, &col2);
+    println!("Part 1: {distance}");
+    println!("Part 2: {similarity}");
+}
 
-    println!("Part1: {sum_diffs}")
+fn part1((col1, col2): &Columns) -> u64 {
+    compare(col1, col2).0
 }
 
-fn part2() {
-    let (col1, col2) = read_pairs();
+fn part2((col1, col2): &Columns) -> u64 {
+    compare(col1, col2).1
+}
 
-    let mut right_counts: HashMap<u64, u64> = HashMap::new();
-    for val in col2.iter() {
-        let count = right_counts.get(val).unwrap_or(&0) + 1;
-        right_counts.insert(*val, count);
+fn read_pairs(input: &str) -> ParseResult<Columns> {
+    let source = Source::new(input);
+    let mut col1: Vec<u64> = Vec::new();
+    let mut col2: Vec<u64> = Vec::new();
+    for line in input.lines() {
+        let [left, right] = source.list_exact(line, "a location id")?;
+        col1.push(left);
+        col2.push(right);
     }
+    radix_sort(&mut col1);
+    radix_sort(&mut col2);
+    Ok((col1, col2))
+}
 
-    let left_score: u64 = col1.iter()
-        .map(|left_val| left_val * right_counts.get(left_val).unwrap_or(&0))
-        .sum();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Part 2: {left_score}");
-}
+    const EXAMPLE: &str = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
 
-fn read_pairs() -> (Vec<u64>, Vec<u64>) {
-    let mut col1: Vec<u64> = Vec::new();
-    let mut col2: Vec<u64> = Vec::new();
-    fs::read_to_string("d01/input")
-        .unwrap()
-        .lines()
-        .for_each(|line| {
-            let mut parts = line.split("   ");
-
-            col1.push(parts.next().unwrap().parse().unwrap());
-            col2.push(parts.next().unwrap().parse().unwrap());
-        });
-    col1.sort();
-    col2.sort();
-    (col1, col2)
-}
\ No newline at end of file
+    #[test]
+    fn example() {
+        let columns = read_pairs(EXAMPLE).unwrap();
+        assert_eq!(part1(&columns), 11);
+        assert_eq!(part2(&columns), 31);
+    }
+
+    #[test]
+    fn any_whitespace() {
+        let (col1, col2) = read_columns("3 4\n4\t3\r\n\n  2   5  \n".as_bytes()).unwrap();
+        assert_eq!((col1, col2), (vec![3, 4, 2], vec![4, 3, 5]));
+        assert!(read_columns("3 4 5\n".as_bytes()).is_err());
+        assert!(read_columns("3 four\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn radix_sorts() {
+        let mut values = vec![70000, 3, u64::MAX, 256, 0, 255, 1 << 40, 3];
+        let mut expected = values.clone();
+        expected.sort();
+        radix_sort(&mut values);
+        assert_eq!(values, expected);
+    }
+}
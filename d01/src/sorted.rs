@@ -0,0 +1,87 @@
+use std::io;
+use std::io::BufRead;
+
+/// Reads `left right` pairs (any whitespace between and around them) a line at a time, so the
+/// input never has to be in memory all at once; only the numbers do.
+pub fn read_columns<R: BufRead>(reader: R) -> io::Result<(Vec<u64>, Vec<u64>)> {
+    let mut col1 = vec![];
+    let mut col2 = vec![];
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let bad_line = || io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected two location ids: {line:?}", idx + 1));
+
+        let mut words = line.split_ascii_whitespace();
+        let (left, right) = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => continue,
+            (Some(left), Some(right), None) => (left, right),
+            _ => return Err(bad_line()),
+        };
+        col1.push(left.parse().map_err(|_| bad_line())?);
+        col2.push(right.parse().map_err(|_| bad_line())?);
+    }
+
+    Ok((col1, col2))
+}
+
+/// LSD radix sort, a byte at a time. Only goes through as many bytes as the biggest value has,
+/// so five-digit location ids take three passes.
+pub fn radix_sort(values: &mut Vec<u64>) {
+    let Some(&max) = values.iter().max() else {
+        return;
+    };
+    let passes = (u64::BITS - max.leading_zeros()).div_ceil(8);
+
+    let mut scratch = vec![0; values.len()];
+    for pass in 0..passes {
+        let shift = pass * 8;
+        let digit = |value: u64| ((value >> shift) & 0xff) as usize;
+
+        let mut starts = [0_usize; 256];
+        for &value in values.iter() {
+            starts[digit(value)] += 1;
+        }
+        let mut total = 0;
+        for start in starts.iter_mut() {
+            (*start, total) = (total, total + *start);
+        }
+
+        for &value in values.iter() {
+            let slot = &mut starts[digit(value)];
+            scratch[*slot] = value;
+            *slot += 1;
+        }
+        std::mem::swap(values, &mut scratch);
+    }
+}
+
+/// The total distance and the similarity score, in one pass over two sorted columns. The
+/// similarity walks through the right column alongside the left, one run of equal ids at a time.
+pub fn compare(col1: &[u64], col2: &[u64]) -> (u64, u64) {
+    let mut distance = 0;
+    let mut similarity = 0;
+    // how far through col2 we've looked, and how many of the last id we saw there
+    let mut right = 0;
+    let mut last: Option<(u64, u64)> = None;
+
+    for (idx, &left) in col1.iter().enumerate() {
+        distance += left.abs_diff(col2[idx]);
+
+        let count = match last {
+            Some((id, count)) if id == left => count,
+            _ => {
+                while right < col2.len() && col2[right] < left {
+                    right += 1;
+                }
+                let run_start = right;
+                while right < col2.len() && col2[right] == left {
+                    right += 1;
+                }
+                (right - run_start) as u64
+            }
+        };
+        last = Some((left, count));
+        similarity += left * count;
+    }
+
+    (distance, similarity)
+}
@@ -1,79 +1,130 @@
-use std::fs;
+use libparse::{ParseResult, Source};
 
-fn main() {
-    part1();
-    part2();
+/// What makes a report safe: every step goes the same way, by `min_step..=max_step`, once at
+/// most `removals` levels have been taken out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Tolerance {
+    min_step: i32,
+    max_step: i32,
+    removals: usize,
 }
 
-fn part1() {
-    let lines = read_input();
-    
-    let safe_count = lines.into_iter()
-        .filter(|line| is_safe(line, 0, false))
-        .count();
-    
-    println!("Part 1: {safe_count}")
+impl Tolerance {
+    fn steps_ok(&self, from: i32, to: i32, increasing: bool) -> bool {
+        let step = if increasing { to - from } else { from - to };
+        (self.min_step..=self.max_step).contains(&step)
+    }
 }
 
-fn part2() {
-    let safe_count = read_input().into_iter()
-        .filter(|line| is_safe(line, 1, true))
-        .count();
-    println!("Part 2: {safe_count}");
+struct Config {
+    // the removals are for part 2; part 1 doesn't get any
+    tolerance: Tolerance,
 }
 
-fn is_safe(line: &[i32], skips: u32, log: bool) -> bool {
-    let (first, remaining) = line.split_first().unwrap();
-    let increasing = *first < remaining[0];
-    let mut prior = first;
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { tolerance: Tolerance { min_step: 1, max_step: 3, removals: 1 } };
 
-    for (idx, current) in remaining.iter().enumerate() {
-        let (cur_min, cur_max) = get_bounds(*prior, increasing);
-        if *current < cur_min || *current > cur_max {
-            if log {
-                println!("INVALID[{skips}]: {:?} - {current} @ {idx}", line);
-            }
-            if skips > 0 {
-                // try skipping this one
-                let without_this = [&line[..idx+1], &line[(idx+2)..]].concat();
-                if is_safe(&without_this, skips - 1, log) {
-                   return true;
-                }
-                // maybe the problem was the prior one instead
-                // ie: 1 2 5 3 4 - 5 is valid, but should be removed, and we don't find out until 3
-                let without_prior = [&line[..idx], &line[idx+1..]].concat();
-                if is_safe(&without_prior, skips - 1, log) {
-                    return true;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--steps" => {
+                    let spec = args.next().ok_or("--steps needs a range, like 1..3")?;
+                    let bad_steps = || format!("Expected a range of steps like 1..3, not {spec:?}");
+                    let (min, max) = spec.split_once("..").ok_or_else(bad_steps)?;
+                    config.tolerance.min_step = min.parse().map_err(|_| bad_steps())?;
+                    config.tolerance.max_step = max.parse().map_err(|_| bad_steps())?;
                 }
-                // maybe the problem was that the 1st entry  set us on the wrong incr/decr path
-                if idx == 1 && is_safe(&line[1..], skips -1, log) {
-                    return true;
+                "--removals" => {
+                    let count = args.next().ok_or("--removals needs a number")?;
+                    config.tolerance.removals = count.parse().map_err(|_| format!("Expected a number of removals, not {count:?}"))?;
                 }
+                _ => return Err(format!("Unrecognized argument {arg:?}; d02 takes --steps and --removals")),
             }
-            return false
         }
-        prior = current;
+
+        Ok(config)
     }
+}
+
+fn main() {
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d02", &message));
 
-    return true
+    runner::run("d02", read_input, |lines| part1(lines, &config), |lines| part2(lines, &config));
 }
 
-fn get_bounds(prior: i32, increasing: bool) -> (i32, i32) {
-    if increasing {
-        (prior + 1, prior + 3)
-    } else {
-        (prior - 3, prior - 1)
+fn part1(lines: &[Vec<i32>], config: &Config) -> usize {
+    let tolerance = Tolerance { removals: 0, ..config.tolerance };
+    lines.iter()
+        .filter(|line| is_safe(line, &tolerance))
+        .count()
+}
+
+fn part2(lines: &[Vec<i32>], config: &Config) -> usize {
+    lines.iter()
+        .filter(|line| dampen(line, &config.tolerance).is_some())
+        .count()
+}
+
+fn is_safe(line: &[i32], tolerance: &Tolerance) -> bool {
+    dampen(line, tolerance).is_some()
+}
+
+/// The fewest levels (by index) to take out of `line` to make it safe, if that's few enough.
+/// Safe reports don't need anything taken out.
+fn dampen(line: &[i32], tolerance: &Tolerance) -> Option<Vec<usize>> {
+    let increasing = fewest_removals(line, tolerance, true);
+    let decreasing = fewest_removals(line, tolerance, false);
+    match (increasing, decreasing) {
+        (Some(up), Some(down)) if down.len() < up.len() => Some(down),
+        (Some(up), _) => Some(up),
+        (None, down) => down,
     }
 }
 
-fn read_input() -> Vec<Vec<i32>> {
-    fs::read_to_string("d02/input")
-        .unwrap().lines()
-        .map(|line| {
-            line.split(" ")
-                .map(|i| i.parse().unwrap())
-                .collect()
-        })
+/// Keeps the longest run of levels it can, going one way: `cost[idx]` is the fewest removals
+/// that leave `line[idx]` as the last level kept so far. Anything kept before that has to be
+/// within `removals + 1` places, or there'd be too much in between, so this is linear in the
+/// length of the report (for a given number of removals).
+fn fewest_removals(line: &[i32], tolerance: &Tolerance, increasing: bool) -> Option<Vec<usize>> {
+    if line.is_empty() {
+        return Some(vec![]);
+    }
+
+    let mut cost = vec![0; line.len()];
+    let mut previous: Vec<Option<usize>> = vec![None; line.len()];
+    for idx in 0..line.len() {
+        // start from here, taking out everything in front
+        cost[idx] = idx;
+        for prior in idx.saturating_sub(tolerance.removals + 1)..idx {
+            let candidate = cost[prior] + (idx - prior - 1);
+            if candidate < cost[idx] && tolerance.steps_ok(line[prior], line[idx], increasing) {
+                cost[idx] = candidate;
+                previous[idx] = Some(prior);
+            }
+        }
+    }
+
+    // ...and then take out everything after the last one kept
+    let last = (0..line.len()).min_by_key(|&idx| cost[idx] + (line.len() - 1 - idx)).unwrap();
+    if cost[last] + (line.len() - 1 - last) > tolerance.removals {
+        return None;
+    }
+
+    let mut kept = vec![false; line.len()];
+    let mut idx = Some(last);
+    while let Some(current) = idx {
+        kept[current] = true;
+        idx = previous[current];
+    }
+    Some((0..line.len()).filter(|&idx| !kept[idx]).collect())
+}
+
+fn read_input(input: &str) -> ParseResult<Vec<Vec<i32>>> {
+    let source = Source::new(input);
+    input.lines()
+        .map(|line| source.list(line, "a level"))
         .collect()
 }
 
@@ -81,39 +132,57 @@ fn read_input() -> Vec<Vec<i32>> {
 mod tests {
     use super::*;
 
+    const ONE_REMOVAL: Tolerance = Tolerance { min_step: 1, max_step: 3, removals: 1 };
+
     #[test]
     fn first_out_of_order_incr() {
         let arr = [2, 1, 2, 3, 4];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
 
     #[test]
     fn first_out_of_order_decr() {
         let arr = [8, 9, 8, 7, 6];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
 
     #[test]
     fn mid_out_of_order_incr() {
         let arr = [1, 2, 3, 5, 4, 5];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
 
     #[test]
     fn mid_out_of_order_decr() {
         let arr = [9, 8, 10, 7, 6];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
 
     #[test]
     fn last_out_of_order_incr() {
         let arr = [1, 2, 3, 4, 1];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
 
     #[test]
     fn last_out_of_order_decr() {
         let arr = [9, 8, 7, 6, 9];
-        assert_eq!(is_safe(&arr, 1, true), true);
+        assert!(is_safe(&arr, &ONE_REMOVAL));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn removed_indices() {
+        assert_eq!(dampen(&[1, 3, 6, 7, 9], &ONE_REMOVAL), Some(vec![]));
+        // taking out the 3 would work too
+        assert_eq!(dampen(&[1, 3, 2, 4, 5], &ONE_REMOVAL), Some(vec![2]));
+        assert_eq!(dampen(&[1, 2, 7, 8, 9], &ONE_REMOVAL), None);
+        // either 4 will do
+        assert_eq!(dampen(&[8, 6, 4, 4, 1], &ONE_REMOVAL), Some(vec![3]));
+
+        let two = Tolerance { removals: 2, ..ONE_REMOVAL };
+        assert_eq!(dampen(&[5, 1, 2, 9, 3, 4], &two), Some(vec![0, 3]));
+        // bigger steps are fine if we say so
+        let wide = Tolerance { min_step: 1, max_step: 5, removals: 0 };
+        assert_eq!(dampen(&[1, 2, 7, 8, 9], &wide), Some(vec![]));
+    }
+}
@@ -1,72 +1,234 @@
-use crate::Instruction::{MUL, START, STOP};
-use lazy_static::lazy_static;
-use regex::{Captures, Regex};
-use std::fs;
-
-lazy_static! {
-    pub static ref MUL_INSTR: Regex = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
-    pub static ref ENABLE_INSTR: Regex = Regex::new(r"do\(\)").unwrap();
-    pub static ref DISABLE_INSTR: Regex = Regex::new(r"don't\(\)").unwrap();
+mod report;
+mod tokenizer;
+
+use crate::report::Report;
+use crate::tokenizer::{Instruction, InstructionSet, Scanned, Tokenizer};
+use libparse::ParseResult;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+struct Config {
+    instructions: InstructionSet,
+    // print the near misses and enabled spans to stderr
+    report: bool,
+    // read this file a chunk at a time, instead of going through the runner
+    stream: Option<PathBuf>,
+}
+
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { instructions: InstructionSet::default(), report: false, stream: None };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--with" => {
+                    let specs = args.next().ok_or("--with needs instructions, like add=sum/2,mul3=product/3")?;
+                    for spec in specs.split(',') {
+                        config.instructions.register_spec(spec)?;
+                    }
+                }
+                "--report" => config.report = true,
+                "--stream" => {
+                    let path = args.next().ok_or("--stream needs a file to read the memory from")?;
+                    config.stream = Some(PathBuf::from(path));
+                }
+                _ => return Err(format!("Unrecognized argument {arg:?}; d03 takes --with, --report, and --stream")),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// What the instructions add up to: all of them, and only the ones that were switched on.
+struct Totals {
+    all: i64,
+    enabled: i64,
 }
 
-enum Instruction {
-    MUL(u64),
-    START,
-    STOP
+/// The memory, once it's been through the tokenizer: the totals, and the near misses and spans
+/// too if there's a report to print.
+struct Program<'a> {
+    totals: Totals,
+    report: Option<Report<'a>>,
 }
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d03", &message));
+
+    if let Some(path) = &config.stream {
+        stream(path, &config);
+        return;
+    }
+
+    // parsing (and so the report) gets repeated when benchmarking, but it only needs saying once
+    let reported = Once::new();
+    let report_then_part1 = |program: &Program| {
+        if let Some(report) = &program.report {
+            reported.call_once(|| eprint!("{report}"));
+        }
+        part1(program)
+    };
+    runner::run("d03", |memory| read_memory(memory, &config), report_then_part1, part2);
+}
+
+/// It's all corrupted anyways; there's nothing that could fail to parse, so this just adds up
+/// the instructions on the way through.
+fn read_memory<'a>(memory: &str, config: &'a Config) -> ParseResult<Program<'a>> {
+    // the memory's already in hand, so reading it can't fail
+    let report = config.report.then(|| Report::build(memory.as_bytes(), &config.instructions).unwrap());
+    let totals = scan(memory.as_bytes(), &config.instructions).unwrap();
+    Ok(Program { totals, report })
+}
+
+/// For memory dumps too big to read in as text: no more than a chunk of the file is ever held
+/// at once. Like d01's `--stream`, this skips the runner, so there's no answer checking,
+/// `--json`, or `--bench`.
+fn stream(path: &Path, config: &Config) {
+    let open = || {
+        File::open(path).map(BufReader::new).unwrap_or_else(|err| {
+            eprintln!("d03: couldn't read {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    };
+    let fail = |err: io::Error| -> ! {
+        eprintln!("d03: {}: {err}", path.display());
+        std::process::exit(1);
+    };
+
+    if config.report {
+        // the report needs a pass of its own
+        eprint!("{}", Report::build(open(), &config.instructions).unwrap_or_else(|err| fail(err)));
+    }
+    let totals = scan(open(), &config.instructions).unwrap_or_else(|err| fail(err));
+
+    println!("Part 1: {}", totals.all);
+    println!("Part 2: {}", totals.enabled);
 }
 
-fn part1() {
-    let input = read_input();
-    let sum: u64 = MUL_INSTR.captures_iter(&input)
-        .map(|hit| get_mul_value(&hit))
-        .sum();
+fn part1(program: &Program) -> i64 {
+    program.totals.all
+}
 
-    println!("Part 1: {sum}");
+fn part2(program: &Program) -> i64 {
+    program.totals.enabled
 }
 
-fn part2() {
-    let input = read_input();
-    
-    // index, instruction
-    let mut all_instr: Vec<(usize, Instruction)> = vec![];
-    MUL_INSTR.captures_iter(&input)
-        .map(|hit| (hit.get(0).unwrap().start(), MUL(get_mul_value(&hit))))
-        .for_each(|instr| all_instr.push(instr));
-    ENABLE_INSTR.captures_iter(&input)
-        .map(|hit| (hit.get(0).unwrap().start(), START))
-        .for_each(|instr| all_instr.push(instr));
-    DISABLE_INSTR.captures_iter(&input)
-        .map(|hit| (hit.get(0).unwrap().start(), STOP))
-        .for_each(|instr| all_instr.push(instr));
-    
-    // sort all instructions by index
-    all_instr.sort_by_key(|i| i.0);
-    
+/// Adds up every well-formed instruction in the corrupted memory as the tokenizer finds them.
+fn scan<R: Read>(memory: R, instructions: &InstructionSet) -> io::Result<Totals> {
     // state machine
-    let mut sum = 0;
+    let mut totals = Totals { all: 0, enabled: 0 };
     let mut enabled = true;
-    all_instr.iter().for_each(|(_, instruction)| match instruction {
-        START => enabled = true,
-        STOP => enabled = false,
-        MUL(val) => if enabled {
-            sum += val;
+    for scanned in Tokenizer::new(memory, instructions) {
+        let Scanned::Token(token) = scanned? else {
+            unreachable!("near misses weren't asked for");
+        };
+        match token.instruction {
+            Instruction::Start => enabled = true,
+            Instruction::Stop => enabled = false,
+            Instruction::Value(val) => {
+                totals.all += val;
+                if enabled {
+                    totals.enabled += val;
+                }
+            }
         }
-    });
-    
-    println!("Part 2: {sum}");
-}
+    }
 
-fn get_mul_value(hit: &Captures) -> u64 {
-    let val1: u64 = hit.get(1).unwrap().as_str().parse().unwrap();
-    let val2: u64 = hit.get(2).unwrap().as_str().parse().unwrap();
-    val1 * val2
+    Ok(totals)
 }
 
-fn read_input() -> String {
-    fs::read_to_string("d03/input").unwrap()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Span;
+    use crate::tokenizer::{Action, Token};
+
+    fn totals(memory: &str, config: &Config) -> (i64, i64) {
+        let program = read_memory(memory, config).unwrap();
+        (part1(&program), part2(&program))
+    }
+
+    const EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn example() {
+        let config = Config::from_args(vec![]).unwrap();
+        assert_eq!(totals(EXAMPLE, &config), (161, 48));
+        assert!(read_memory(EXAMPLE, &config).unwrap().report.is_none());
+
+        let config = Config::from_args(vec![String::from("--report")]).unwrap();
+        let report = read_memory(EXAMPLE, &config).unwrap().report.unwrap();
+        assert_eq!(report.near_misses.len(), 2);
+    }
+
+    #[test]
+    fn chunk_sizes_dont_matter() {
+        let instructions = InstructionSet::default();
+        let whole: Vec<Scanned> = Tokenizer::new(EXAMPLE.as_bytes(), &instructions)
+            .with_near_misses()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(whole.len(), 8);
+        assert_eq!(whole[2], Scanned::Token(Token { offset: 20, name: "don't", instruction: Instruction::Stop }));
+
+        for chunk_size in [1, 2, 7, 100] {
+            let chunked: Vec<Scanned> = Tokenizer::with_chunk_size(EXAMPLE.as_bytes(), &instructions, chunk_size)
+                .with_near_misses()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(chunked, whole, "chunks of {chunk_size}");
+        }
+    }
+
+    #[test]
+    fn streams_files() {
+        let path = std::env::temp_dir().join(format!("d03-stream-{}.txt", std::process::id()));
+        std::fs::write(&path, EXAMPLE.repeat(1000)).unwrap();
+        let file = BufReader::new(File::open(&path).unwrap());
+        let totals = scan(file, &InstructionSet::default());
+        std::fs::remove_file(&path).unwrap();
+
+        let totals = totals.unwrap();
+        assert_eq!((totals.all, totals.enabled), (161 * 1000, 48 * 1000));
+    }
+
+    #[test]
+    fn registered_instructions() {
+        let mut config = Config::from_args(vec![String::from("--with"), String::from("add=sum/2,sub=difference/2")]).unwrap();
+        config.instructions.register("mul3", 3, Action::Product).unwrap();
+        assert!(config.instructions.register_spec("mul=sum/2").is_err());
+        assert!(config.instructions.register_spec("add3=sum").is_err());
+
+        let memory = "add(1,2)sub(3,10)mul3(2,3,4)mul3(2,3)don't()add(100,1)";
+        assert_eq!(totals(memory, &config), (3 - 7 + 24 + 101, 3 - 7 + 24));
+    }
+
+    #[test]
+    fn reports() {
+        let instructions = InstructionSet::default();
+        let memory = "mul(4*mul ( 2 , 4 )don't()mul(1234,5)do()mul(2,3)don't(x";
+        let report = Report::build(memory.as_bytes(), &instructions).unwrap();
+
+        let near_misses: Vec<(u64, &str, String)> = report.near_misses.iter()
+            .map(|near_miss| (near_miss.offset, near_miss.text.as_str(), near_miss.reason.to_string()))
+            .collect();
+        assert_eq!(near_misses, vec![
+            (0, "mul(4*", String::from("expected ',' after argument 1, found '*'")),
+            (6, "mul ", String::from("expected '(' right after the name, found a space")),
+            (26, "mul(1234", String::from("argument 1 has more than 3 digits")),
+            (49, "don't(x", String::from("expected ')', found 'x'")),
+        ]);
+        assert_eq!(report.spans, vec![
+            Span { start: 0, end: 19, enabled: true },
+            Span { start: 19, end: 37, enabled: false },
+            // the last don't() never quite happened
+            Span { start: 37, end: 56, enabled: true },
+        ]);
+    }
+}
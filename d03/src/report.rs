@@ -0,0 +1,71 @@
+use crate::tokenizer::{Instruction, InstructionSet, NearMiss, Scanned, Tokenizer};
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::Read;
+
+/// A stretch of memory where instructions either count or don't: `start..end`, in bytes.
+#[derive(Debug, PartialEq)]
+pub struct Span {
+    pub start: u64,
+    pub end: u64,
+    pub enabled: bool,
+}
+
+/// Everything that went wrong in a memory dump (or nearly went right), plus where the
+/// instructions were switched on and off.
+#[derive(Debug, PartialEq)]
+pub struct Report<'a> {
+    pub near_misses: Vec<NearMiss<'a>>,
+    pub spans: Vec<Span>,
+}
+
+impl<'a> Report<'a> {
+    pub fn build<R: Read>(memory: R, instructions: &'a InstructionSet) -> io::Result<Report<'a>> {
+        let mut near_misses = vec![];
+        let mut spans = vec![];
+        let mut current = Span { start: 0, end: 0, enabled: true };
+
+        let mut tokenizer = Tokenizer::new(memory, instructions).with_near_misses();
+        for scanned in tokenizer.by_ref() {
+            let token = match scanned? {
+                Scanned::Token(token) => token,
+                Scanned::NearMiss(near_miss) => {
+                    near_misses.push(near_miss);
+                    continue;
+                }
+            };
+
+            let enabled = match token.instruction {
+                Instruction::Start => true,
+                Instruction::Stop => false,
+                Instruction::Value(_) => continue,
+            };
+            if enabled != current.enabled {
+                let next = Span { start: token.offset, end: 0, enabled };
+                spans.push(Span { end: token.offset, ..current });
+                current = next;
+            }
+        }
+        spans.push(Span { end: tokenizer.offset(), ..current });
+        spans.retain(|span| span.start < span.end);
+
+        Ok(Report { near_misses, spans })
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} near misses:", self.near_misses.len())?;
+        for near_miss in self.near_misses.iter() {
+            writeln!(f, "  at byte {}: {:?} {}", near_miss.offset, near_miss.text, near_miss.reason)?;
+        }
+
+        writeln!(f, "{} spans:", self.spans.len())?;
+        for span in self.spans.iter() {
+            let state = if span.enabled { "enabled" } else { "disabled" };
+            writeln!(f, "  {}..{} {state}", span.start, span.end)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::Read;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    Value(i64),
+    Start,
+    Stop,
+}
+
+/// What an instruction does with its arguments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Sum,
+    Product,
+    /// The first argument, minus all the rest
+    Difference,
+    Start,
+    Stop,
+}
+
+impl Action {
+    fn parse(name: &str) -> Result<Action, String> {
+        match name {
+            "sum" => Ok(Action::Sum),
+            "product" => Ok(Action::Product),
+            "difference" => Ok(Action::Difference),
+            "start" => Ok(Action::Start),
+            "stop" => Ok(Action::Stop),
+            _ => Err(format!("Unknown action {name:?}; expected sum, product, difference, start, or stop")),
+        }
+    }
+
+    /// None if the numbers get too big.
+    fn apply(self, args: &[i64]) -> Option<Instruction> {
+        let mut rest = args.iter().copied();
+        let value = match self {
+            Action::Start => return Some(Instruction::Start),
+            Action::Stop => return Some(Instruction::Stop),
+            Action::Sum => rest.try_fold(0_i64, i64::checked_add),
+            Action::Product => rest.try_fold(1_i64, i64::checked_mul),
+            Action::Difference => {
+                let first = rest.next().unwrap_or(0);
+                rest.try_fold(first, i64::checked_sub)
+            }
+        };
+        value.map(Instruction::Value)
+    }
+}
+
+struct Spec {
+    name: String,
+    arity: usize,
+    action: Action,
+}
+
+/// Arguments are 1-3 digits, no more, no less.
+pub const MAX_DIGITS: usize = 3;
+
+/// The instructions the tokenizer knows about: `name(a,b,...)`, with exactly `arity` arguments.
+pub struct InstructionSet {
+    specs: Vec<Spec>,
+}
+
+impl Default for InstructionSet {
+    /// `mul(a,b)`, `do()`, and `don't()`
+    fn default() -> InstructionSet {
+        let mut set = InstructionSet { specs: vec![] };
+        set.register("mul", 2, Action::Product).unwrap();
+        set.register("do", 0, Action::Start).unwrap();
+        set.register("don't", 0, Action::Stop).unwrap();
+        set
+    }
+}
+
+impl InstructionSet {
+    pub fn register(&mut self, name: &str, arity: usize, action: Action) -> Result<(), String> {
+        if name.is_empty() || name.contains(['(', ')', ',']) {
+            return Err(format!("{name:?} can't be an instruction name"));
+        }
+        if self.specs.iter().any(|spec| spec.name == name) {
+            return Err(format!("There's already an instruction called {name:?}"));
+        }
+
+        self.specs.push(Spec { name: name.to_string(), arity, action });
+        Ok(())
+    }
+
+    /// `NAME=ACTION/ARITY`, like `add=sum/2` or `mul3=product/3`
+    pub fn register_spec(&mut self, spec: &str) -> Result<(), String> {
+        let bad_spec = || format!("Expected NAME=ACTION/ARITY, like add=sum/2, not {spec:?}");
+        let (name, rest) = spec.split_once('=').ok_or_else(bad_spec)?;
+        let (action, arity) = rest.split_once('/').ok_or_else(bad_spec)?;
+        let arity = arity.parse().map_err(|_| bad_spec())?;
+        self.register(name, arity, Action::parse(action)?)
+    }
+
+    /// The most bytes an instruction could take up, which is as far ahead as the tokenizer ever
+    /// needs to look.
+    fn longest(&self) -> usize {
+        self.specs.iter()
+            .map(|spec| spec.name.len() + 2 + spec.arity * (MAX_DIGITS + 1))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// What's right at the start of `window`: an instruction, something that starts out like
+    /// one, or neither.
+    fn match_at(&self, window: &[u8]) -> Match<'_> {
+        let mut near_miss: Option<(&Spec, Rejection, usize)> = None;
+        for spec in self.specs.iter() {
+            let Some(rest) = window.strip_prefix(spec.name.as_bytes()) else {
+                continue;
+            };
+            let result = call_args(rest, spec.arity)
+                .and_then(|(args, len)| spec.action.apply(&args).map(|instruction| (instruction, len)).ok_or((Rejection::Overflow, len)));
+            match result {
+                Ok((instruction, len)) => return Match::Found(&spec.name, instruction, spec.name.len() + len),
+                // when several names fit, the longest one is what it was most likely meant to be
+                Err((reason, len)) if near_miss.is_none_or(|(other, _, _)| other.name.len() < spec.name.len()) => {
+                    near_miss = Some((spec, reason, spec.name.len() + len));
+                }
+                Err(_) => {}
+            }
+        }
+
+        match near_miss {
+            Some((spec, reason, len)) => Match::NearMiss(&spec.name, reason, len),
+            None => Match::Nothing,
+        }
+    }
+}
+
+enum Match<'a> {
+    Found(&'a str, Instruction, usize),
+    // how far it got, counting the byte that gave it away
+    NearMiss(&'a str, Rejection, usize),
+    Nothing,
+}
+
+/// Why something that started out like an instruction isn't one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rejection {
+    /// The name wasn't followed right away by `(`
+    NoParen { found: Option<u8> },
+    NoDigits { arg: usize, found: Option<u8> },
+    TooManyDigits { arg: usize },
+    NoComma { arg: usize, found: Option<u8> },
+    NoClose { found: Option<u8> },
+    /// Everything's in the right place, but the answer doesn't fit
+    Overflow,
+}
+
+/// The offending byte, as it would appear in the memory; None is running out of memory.
+fn describe(found: Option<u8>) -> String {
+    match found {
+        Some(byte) if byte.is_ascii_graphic() => format!("'{}'", byte as char),
+        Some(b' ') => String::from("a space"),
+        Some(byte) => format!("byte 0x{byte:02x}"),
+        None => String::from("the end of the memory"),
+    }
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::NoParen { found } => write!(f, "expected '(' right after the name, found {}", describe(*found)),
+            Rejection::NoDigits { arg, found } => write!(f, "expected a number for argument {arg}, found {}", describe(*found)),
+            Rejection::TooManyDigits { arg } => write!(f, "argument {arg} has more than {MAX_DIGITS} digits"),
+            Rejection::NoComma { arg, found } => write!(f, "expected ',' after argument {arg}, found {}", describe(*found)),
+            Rejection::NoClose { found } => write!(f, "expected ')', found {}", describe(*found)),
+            Rejection::Overflow => write!(f, "the result is too big"),
+        }
+    }
+}
+
+/// `(a,b,...)` with exactly `arity` arguments, and how many bytes that was. If it's not, why
+/// not, and how many bytes it took to find out.
+fn call_args(text: &[u8], arity: usize) -> Result<(Vec<i64>, usize), (Rejection, usize)> {
+    let found = |pos: usize| text.get(pos).copied();
+    // through the byte that gave it away, if there was one
+    let upto = |pos: usize| (pos + 1).min(text.len());
+
+    let mut pos = 1;
+    if found(0) != Some(b'(') {
+        return Err((Rejection::NoParen { found: found(0) }, upto(0)));
+    }
+
+    let mut args = Vec::with_capacity(arity);
+    for idx in 0..arity {
+        let arg = idx + 1;
+        if idx > 0 {
+            if found(pos) != Some(b',') {
+                return Err((Rejection::NoComma { arg: idx, found: found(pos) }, upto(pos)));
+            }
+            pos += 1;
+        }
+        // one too many is enough to know
+        let digits = text[pos..].iter().take(MAX_DIGITS + 1).take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err((Rejection::NoDigits { arg, found: found(pos) }, upto(pos)));
+        }
+        if digits > MAX_DIGITS {
+            return Err((Rejection::TooManyDigits { arg }, pos + digits));
+        }
+        args.push(text[pos..pos + digits].iter().fold(0, |acc, b| acc * 10 + (b - b'0') as i64));
+        pos += digits;
+    }
+
+    match found(pos) {
+        Some(b')') => Ok((args, pos + 1)),
+        found => Err((Rejection::NoClose { found }, upto(pos))),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
+    /// Bytes from the very start of the input
+    pub offset: u64,
+    pub name: &'a str,
+    pub instruction: Instruction,
+}
+
+/// Something that looked like it was going to be an instruction, but wasn't.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearMiss<'a> {
+    pub offset: u64,
+    pub name: &'a str,
+    /// From the start of the name through whatever gave it away
+    pub text: String,
+    pub reason: Rejection,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scanned<'a> {
+    Token(Token<'a>),
+    NearMiss(NearMiss<'a>),
+}
+
+/// Reads instructions out of corrupted memory a chunk at a time, so it never holds more than a
+/// chunk (plus one instruction's worth of lookahead) in memory. Everything that isn't an
+/// instruction gets skipped.
+pub struct Tokenizer<'a, R> {
+    reader: R,
+    set: &'a InstructionSet,
+    chunk_size: usize,
+    // how much has to be in the buffer before we look for instructions
+    lookahead: usize,
+    buffer: Vec<u8>,
+    // where we're up to in the buffer
+    pos: usize,
+    // how far into the input the buffer starts
+    buffer_offset: u64,
+    done_reading: bool,
+    near_misses: bool,
+}
+
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+impl<'a, R: Read> Tokenizer<'a, R> {
+    pub fn new(reader: R, set: &'a InstructionSet) -> Tokenizer<'a, R> {
+        Tokenizer::with_chunk_size(reader, set, CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(reader: R, set: &'a InstructionSet, chunk_size: usize) -> Tokenizer<'a, R> {
+        Tokenizer {
+            reader,
+            set,
+            chunk_size: chunk_size.max(1),
+            lookahead: set.longest(),
+            buffer: vec![],
+            pos: 0,
+            buffer_offset: 0,
+            done_reading: false,
+            near_misses: false,
+        }
+    }
+
+    /// Report near misses too, instead of skipping over them.
+    pub fn with_near_misses(self) -> Tokenizer<'a, R> {
+        Tokenizer { near_misses: true, ..self }
+    }
+
+    /// How far into the input we've gotten.
+    pub fn offset(&self) -> u64 {
+        self.buffer_offset + self.pos as u64
+    }
+
+    /// Drops what we've already been through, then reads another chunk onto the end.
+    fn fill(&mut self) -> io::Result<()> {
+        self.buffer.drain(..self.pos);
+        self.buffer_offset += self.pos as u64;
+        self.pos = 0;
+
+        let len = self.buffer.len();
+        self.buffer.resize(len + self.chunk_size, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        let read = read.inspect_err(|_| self.buffer.truncate(len))?;
+        self.buffer.truncate(len + read);
+        self.done_reading = read == 0;
+
+        Ok(())
+    }
+}
+
+impl<'a, R: Read> Iterator for Tokenizer<'a, R> {
+    type Item = io::Result<Scanned<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if !self.done_reading && self.buffer.len() - self.pos < self.lookahead {
+                if let Err(err) = self.fill() {
+                    return Some(Err(err));
+                }
+                continue;
+            }
+            if self.pos >= self.buffer.len() {
+                return None;
+            }
+
+            let set = self.set;
+            let offset = self.buffer_offset + self.pos as u64;
+            let window = &self.buffer[self.pos..];
+            match set.match_at(window) {
+                Match::Found(name, instruction, len) => {
+                    self.pos += len;
+                    return Some(Ok(Scanned::Token(Token { offset, name, instruction })));
+                }
+                Match::NearMiss(name, reason, len) if self.near_misses => {
+                    let text = String::from_utf8_lossy(&window[..len]).into_owned();
+                    // the thing that gave it away could be the start of something real
+                    self.pos += 1;
+                    return Some(Ok(Scanned::NearMiss(NearMiss { offset, name, text, reason })));
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
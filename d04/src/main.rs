@@ -1,102 +1,154 @@
-use lib2d::{corners, Point2d};
-use std::collections::HashMap;
+mod search;
+
+use crate::search::{Stencil, WordSearch};
+use lib2d::Grid;
+use libparse::{ParseResult, Source};
 use std::fs;
 
-fn main() {
-    part1();
-    part2();
+type Puzzle = Grid<char>;
+
+/// The X-MAS cross; its rotations and reflections come for free.
+const X_MAS: &str = include_str!("../patterns/x-mas.txt");
+
+struct Config {
+    words: Vec<String>,
+    stencils: Vec<Stencil>,
+    // print every hit to stderr
+    hits: bool,
 }
 
-fn part1() {
-    let puzzle = read_puzzle();
-    let (min, max) = corners(puzzle.keys()).unwrap();
-
-    let deltas = [
-        Point2d::new(-1, -1),
-        Point2d::new(-1, 0),
-        Point2d::new(-1, 1),
-        Point2d::new(0, -1),
-        Point2d::new(0, 1),
-        Point2d::new(1, -1),
-        Point2d::new(1, 0),
-        Point2d::new(1, 1)
-    ];
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { words: vec![String::from("XMAS")], stencils: Stencil::parse_all(X_MAS)?, hits: false };
 
-    let mut count = 0;
-    for x in min.x..=max.x {
-        for y in min.y..=max.y {
-            let cur_pos = Point2d::new(x, y);
-            for delta in deltas {
-                if check_xmas(&puzzle, &cur_pos, &delta) {
-                    count += 1
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--words" => {
+                    let words = args.next().ok_or("--words needs some words, like XMAS,SANTA")?;
+                    config.words = words.split(',').map(String::from).collect();
+                }
+                "--patterns" => {
+                    let path = args.next().ok_or("--patterns needs a pattern file")?;
+                    let text = fs::read_to_string(&path).map_err(|err| format!("couldn't read {path}: {err}"))?;
+                    config.stencils = Stencil::parse_all(&text).map_err(|err| format!("{path}: {err}"))?;
                 }
+                "--hits" => config.hits = true,
+                _ => return Err(format!("Unrecognized argument {arg:?}; d04 takes --words, --patterns, and --hits")),
             }
         }
+
+        Ok(config)
     }
+}
 
-    println!("Part 1: {count}");
+fn main() {
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d04", &message));
+
+    runner::run("d04", read_puzzle, |puzzle| part1(puzzle, &config), |puzzle| part2(puzzle, &config));
 }
 
-fn check_xmas(puzzle: &HashMap<Point2d<i32>, char>,
-              cur_pos: &Point2d<i32>,
-              delta: &Point2d<i32>) -> bool {
-    let mut cur_pos = cur_pos.clone();
-    for letter in "XMAS".chars() {
-        if puzzle.get(&cur_pos) != Some(&letter) {
-            return false;
+fn part1(puzzle: &Puzzle, config: &Config) -> usize {
+    let search = WordSearch::new(&config.words);
+    let hits = search.find(puzzle);
+    if config.hits {
+        for hit in hits.iter() {
+            eprintln!("{} at {},{} heading {:?}", hit.word, hit.start.x, hit.start.y, hit.heading);
         }
-        cur_pos = cur_pos + *delta;
     }
 
-    return true;
+    hits.len()
 }
 
-fn part2() {
-    let puzzle = read_puzzle();
-    let (min, max) = corners(puzzle.keys()).unwrap();
-
-    let deltas = [
-        Point2d::new(-1, -1),
-        Point2d::new(-1, 1),
-        Point2d::new(1, -1),
-        Point2d::new(1, 1)
-    ];
-
+fn part2(puzzle: &Puzzle, config: &Config) -> usize {
     let mut count = 0;
-    for x in min.x..=max.x {
-        for y in min.y..=max.y {
-            let cur_point = Point2d::new(x, y);
-            if puzzle.get(&cur_point) == Some(&'A') {
-                let mases = deltas.iter()
-                    .filter(|delta| is_mas(&puzzle, &cur_point, delta))
-                    .count();
-                if mases == 2 {
-                    count += 1;
+    for stencil in config.stencils.iter() {
+        for variant in stencil.variants() {
+            let found = variant.find(puzzle);
+            if config.hits {
+                for point in found.iter() {
+                    eprintln!("{variant} at {},{}", point.x, point.y);
                 }
             }
+            count += found.len();
         }
     }
 
-    println!("Part 2: {count}");
+    count
 }
 
-fn is_mas(puzzle: &HashMap<Point2d<i32>, char>, 
-          cur_point: &Point2d<i32>, 
-          delta: &Point2d<i32>) -> bool {
-    let m_pos = *cur_point + *delta;
-    let s_pos = *cur_point - *delta;
-    
-    puzzle.get(&m_pos) == Some(&'M') && puzzle.get(&s_pos) == Some(&'S')
+fn read_puzzle(input: &str) -> ParseResult<Puzzle> {
+    let source = Source::new(input);
+    // any letter is a valid letter
+    let mut rows: Vec<Vec<char>> = vec![];
+    for line in input.lines() {
+        let row: Vec<char> = line.chars().collect();
+        if rows.first().is_some_and(|first| first.len() != row.len()) {
+            return Err(source.error(line, format!("a row {} letters wide", rows[0].len())));
+        }
+        rows.push(row);
+    }
+
+    Ok(Grid::from_rows(rows).unwrap())
 }
 
-fn read_puzzle() -> HashMap<Point2d<i32>, char> {
-    fs::read_to_string("d04/input").unwrap()
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(x, char)| (Point2d::new(x as i32, y as i32), char))
-        })
-        .collect()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{Heading, Hit};
+    use lib2d::Point2d;
+
+    const EXAMPLE: &str = "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+                           XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX\n";
+
+    #[test]
+    fn example() {
+        let puzzle = read_puzzle(EXAMPLE).unwrap();
+        let config = Config::from_args(vec![]).unwrap();
+        assert_eq!(part1(&puzzle, &config), 18);
+        assert_eq!(part2(&puzzle, &config), 9);
+    }
+
+    #[test]
+    fn several_words() {
+        let puzzle = read_puzzle("CAT\nXAX\nTOP\n").unwrap();
+        let search = WordSearch::new(&["CAT", "CA", "TOP", "TAC", "CAT"]);
+        let hits = search.find(&puzzle);
+
+        assert_eq!(hits.len(), 5);
+        assert!(hits.contains(&Hit { start: Point2d::new(0, 0), heading: Heading::East, word: "CA" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(0, 0), heading: Heading::SouthEast, word: "CA" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(0, 0), heading: Heading::East, word: "CAT" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(2, 0), heading: Heading::West, word: "TAC" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(0, 2), heading: Heading::East, word: "TOP" }));
+    }
+
+    #[test]
+    fn one_letter_words() {
+        let puzzle = read_puzzle("CAT\nXAX\nTOP\n").unwrap();
+        let search = WordSearch::new(&["A", "CAT"]);
+        let hits = search.find(&puzzle);
+
+        assert_eq!(hits.len(), 3);
+        assert!(hits.contains(&Hit { start: Point2d::new(1, 0), heading: Heading::North, word: "A" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(1, 1), heading: Heading::North, word: "A" }));
+        assert!(hits.contains(&Hit { start: Point2d::new(0, 0), heading: Heading::East, word: "CAT" }));
+    }
+
+    #[test]
+    fn stencil_variants() {
+        let x_mas = Stencil::parse(X_MAS).unwrap();
+        assert_eq!(x_mas.variants().len(), 4);
+        assert_eq!(x_mas.variants()[1].to_string(), "S.M/.A./S.M");
+
+        // an L has all eight
+        let ell = Stencil::parse("A.\nB.\nCD\n").unwrap();
+        assert_eq!(ell.variants().len(), 8);
+        assert!(Stencil::parse_all("...\n\n.").is_err());
+
+        let puzzle = read_puzzle("XAX\nXBX\nDCX\n").unwrap();
+        let found: Vec<Point2d<i32>> = ell.variants().iter().flat_map(|variant| variant.find(&puzzle)).collect();
+        assert_eq!(found, vec![Point2d::new(0, 0)]);
+    }
+}
@@ -0,0 +1,225 @@
+use lib2d::{Grid, Point2d};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The eight ways a word can run through the grid. Rows count down, so north is -y.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Heading {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Heading {
+    pub const ALL: [Heading; 8] = [
+        Heading::North,
+        Heading::NorthEast,
+        Heading::East,
+        Heading::SouthEast,
+        Heading::South,
+        Heading::SouthWest,
+        Heading::West,
+        Heading::NorthWest,
+    ];
+
+    pub fn delta(self) -> Point2d<i32> {
+        match self {
+            Heading::North => Point2d::new(0, -1),
+            Heading::NorthEast => Point2d::new(1, -1),
+            Heading::East => Point2d::new(1, 0),
+            Heading::SouthEast => Point2d::new(1, 1),
+            Heading::South => Point2d::new(0, 1),
+            Heading::SouthWest => Point2d::new(-1, 1),
+            Heading::West => Point2d::new(-1, 0),
+            Heading::NorthWest => Point2d::new(-1, -1),
+        }
+    }
+}
+
+/// A word found in the grid: it starts at `start` and reads off towards `heading`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hit<'a> {
+    pub start: Point2d<i32>,
+    pub heading: Heading,
+    pub word: &'a str,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    // which word ends here, if any
+    word: Option<usize>,
+}
+
+/// Looks for any number of words at once: every word lives in one trie, so each walk from a
+/// starting point checks them all together and stops as soon as nothing could match.
+pub struct WordSearch {
+    words: Vec<String>,
+    nodes: Vec<TrieNode>,
+}
+
+impl WordSearch {
+    pub fn new<S: AsRef<str>>(words: &[S]) -> WordSearch {
+        let mut search = WordSearch { words: vec![], nodes: vec![TrieNode::default()] };
+        for word in words {
+            search.insert(word.as_ref());
+        }
+        search
+    }
+
+    fn insert(&mut self, word: &str) {
+        let mut node = 0;
+        for char in word.chars() {
+            node = match self.nodes[node].children.get(&char) {
+                Some(&child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(char, child);
+                    child
+                }
+            };
+        }
+
+        // the same word twice is still just the one word
+        if self.nodes[node].word.is_none() && !word.is_empty() {
+            self.words.push(word.to_string());
+            self.nodes[node].word = Some(self.words.len() - 1);
+        }
+    }
+
+    /// Every word, every place it starts, and every way it reads from there. One-letter words
+    /// don't really read any way, so they show up once, heading north.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<Hit<'_>> {
+        let mut hits = vec![];
+        for (start, _) in grid.iter() {
+            for heading in Heading::ALL {
+                let mut node = 0;
+                let mut pos = start;
+                while let Some(&child) = grid.get(pos).and_then(|char| self.nodes[node].children.get(char)) {
+                    node = child;
+                    let once = pos != start || heading == Heading::ALL[0];
+                    if let Some(word) = self.nodes[node].word.filter(|_| once) {
+                        hits.push(Hit { start, heading, word: &self.words[word] });
+                    }
+                    pos = pos + heading.delta();
+                }
+            }
+        }
+
+        hits
+    }
+}
+
+/// Matches anything in a stencil.
+pub const WILDCARD: char = '.';
+
+/// A little 2D picture of letters to find in the grid. Wildcards (and spaces, or anything past
+/// the end of a short row) match any letter, so only the letters that matter get kept, relative
+/// to the top-left corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stencil {
+    cells: Vec<(Point2d<i32>, char)>,
+}
+
+impl Stencil {
+    pub fn parse(text: &str) -> Result<Stencil, String> {
+        let cells = text.lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, char)| *char != WILDCARD && !char.is_whitespace())
+                    .map(move |(x, char)| (Point2d::new(x as i32, y as i32), char))
+            })
+            .collect();
+
+        match Stencil::normalized(cells) {
+            Some(stencil) => Ok(stencil),
+            None => Err(format!("A stencil needs at least one letter, not just {WILDCARD:?}s: {text:?}")),
+        }
+    }
+
+    /// A pattern file is just stencils, with blank lines between them.
+    pub fn parse_all(text: &str) -> Result<Vec<Stencil>, String> {
+        let stencils: Vec<Stencil> = text.split("\n\n")
+            .filter(|block| !block.trim().is_empty())
+            .map(Stencil::parse)
+            .collect::<Result<_, _>>()?;
+        if stencils.is_empty() {
+            return Err(String::from("No stencils in the pattern file"));
+        }
+
+        Ok(stencils)
+    }
+
+    /// Slid up against the top-left corner and sorted, so equal shapes compare equal.
+    fn normalized(mut cells: Vec<(Point2d<i32>, char)>) -> Option<Stencil> {
+        let min_x = cells.iter().map(|(point, _)| point.x).min()?;
+        let min_y = cells.iter().map(|(point, _)| point.y).min()?;
+        for (point, _) in cells.iter_mut() {
+            *point = *point - Point2d::new(min_x, min_y);
+        }
+        cells.sort_by_key(|(point, _)| *point);
+
+        Some(Stencil { cells })
+    }
+
+    fn transformed(&self, f: impl Fn(Point2d<i32>) -> Point2d<i32>) -> Stencil {
+        Stencil::normalized(self.cells.iter().map(|(point, char)| (f(*point), *char)).collect()).unwrap()
+    }
+
+    /// Every rotation and reflection, without repeats.
+    pub fn variants(&self) -> Vec<Stencil> {
+        let mut variants: Vec<Stencil> = vec![];
+        let mut turned = self.clone();
+        for _ in 0..4 {
+            let flipped = turned.transformed(|point| Point2d::new(-point.x, point.y));
+            for variant in [turned.clone(), flipped] {
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+            turned = turned.transformed(|point| Point2d::new(-point.y, point.x));
+        }
+
+        variants
+    }
+
+    pub fn matches_at(&self, grid: &Grid<char>, at: Point2d<i32>) -> bool {
+        self.cells.iter().all(|(offset, char)| grid.get(at + *offset) == Some(char))
+    }
+
+    /// Everywhere in the grid this exact stencil (not its variants) fits.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<Point2d<i32>> {
+        grid.iter()
+            .map(|(point, _)| point)
+            .filter(|point| self.matches_at(grid, *point))
+            .collect()
+    }
+}
+
+/// One line, with `/` between the rows.
+impl Display for Stencil {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.cells.iter().map(|(point, _)| point.x).max().unwrap_or(0) + 1;
+        let height = self.cells.iter().map(|(point, _)| point.y).max().unwrap_or(0) + 1;
+        let rows: Vec<String> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let point = Point2d::new(x, y);
+                        self.cells.iter().find(|(cell, _)| *cell == point).map_or(WILDCARD, |(_, char)| *char)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        write!(f, "{}", rows.join("/"))
+    }
+}
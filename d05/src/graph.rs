@@ -0,0 +1,126 @@
+use crate::Rule;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+/// The rules that matter for one section, as a graph: an edge from each rule's `before` page to
+/// its `after` page.
+pub struct PageGraph<'a> {
+    pages: Vec<u64>,
+    // each page's outgoing rules, by index into `pages`
+    edges: Vec<Vec<(usize, &'a Rule)>>,
+}
+
+/// A topological order of the pages.
+#[derive(Debug, PartialEq)]
+pub struct Ordering {
+    pub pages: Vec<u64>,
+    /// Whether it's the only order that follows the rules
+    pub unique: bool,
+}
+
+/// Rules that chase each other around in a circle, each one's `after` being the next one's
+/// `before`.
+#[derive(Debug, PartialEq)]
+pub struct Cycle {
+    pub rules: Vec<Rule>,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rules: Vec<String> = self.rules.iter().map(|rule| rule.to_string()).collect();
+        write!(f, "the rules {} go in a circle", rules.join(", "))
+    }
+}
+
+/// Two pages in the wrong order, and the rule that says so.
+#[derive(Debug, PartialEq)]
+pub struct Violation<'a> {
+    pub earlier: u64,
+    pub later: u64,
+    pub rule: &'a Rule,
+}
+
+impl<'a> PageGraph<'a> {
+    /// Only the rules between pages in `section` matter.
+    pub fn new(rules: &'a [Rule], section: &[u64]) -> PageGraph<'a> {
+        let mut pages = vec![];
+        let mut index = HashMap::new();
+        for &page in section {
+            index.entry(page).or_insert_with(|| {
+                pages.push(page);
+                pages.len() - 1
+            });
+        }
+
+        let mut edges = vec![vec![]; pages.len()];
+        for rule in rules {
+            if let (Some(&before), Some(&after)) = (index.get(&rule.before), index.get(&rule.after)) {
+                edges[before].push((after, rule));
+            }
+        }
+
+        PageGraph { pages, edges }
+    }
+
+    /// Kahn's algorithm: keep taking a page that nothing remaining has to come before. If more
+    /// than one is ever available, there's more than one order.
+    pub fn sort(&self) -> Result<Ordering, Cycle> {
+        let mut incoming = vec![0; self.pages.len()];
+        for &(after, _) in self.edges.iter().flatten() {
+            incoming[after] += 1;
+        }
+
+        let mut ready: VecDeque<usize> = (0..self.pages.len()).filter(|&page| incoming[page] == 0).collect();
+        let mut order = vec![];
+        let mut unique = true;
+        while let Some(page) = ready.pop_front() {
+            unique &= ready.is_empty();
+            order.push(self.pages[page]);
+            for &(after, _) in &self.edges[page] {
+                incoming[after] -= 1;
+                if incoming[after] == 0 {
+                    ready.push_back(after);
+                }
+            }
+        }
+
+        if order.len() < self.pages.len() {
+            return Err(self.find_cycle(&incoming));
+        }
+        Ok(Ordering { pages: order, unique })
+    }
+
+    /// Everything Kahn couldn't get to still has something in front of it, so walking backwards
+    /// through those has to come back around eventually.
+    fn find_cycle(&self, incoming: &[usize]) -> Cycle {
+        let stuck = |page: usize| incoming[page] > 0;
+        let mut previous: Vec<Option<(usize, &Rule)>> = vec![None; self.pages.len()];
+        for (before, edges) in self.edges.iter().enumerate().filter(|(before, _)| stuck(*before)) {
+            for &(after, rule) in edges.iter().filter(|(after, _)| stuck(*after)) {
+                previous[after] = Some((before, rule));
+            }
+        }
+
+        let mut seen = vec![false; self.pages.len()];
+        let mut page = (0..self.pages.len()).find(|&page| stuck(page)).unwrap();
+        while !seen[page] {
+            seen[page] = true;
+            page = previous[page].unwrap().0;
+        }
+
+        // `page` is on the cycle now; go around it once
+        let mut rules = vec![];
+        let start = page;
+        loop {
+            let (before, rule) = previous[page].unwrap();
+            rules.push(rule.clone());
+            page = before;
+            if page == start {
+                break;
+            }
+        }
+        rules.reverse();
+
+        Cycle { rules }
+    }
+}
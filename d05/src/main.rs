@@ -1,115 +1,314 @@
-use std::collections::{HashMap, HashSet};
-use std::fs;
+mod graph;
 
-#[derive(Clone)]
+use crate::graph::{Cycle, PageGraph, Violation};
+use libparse::{ParseResult, Source};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
 struct Rule {
     pub before: u64,
     pub after: u64,
 }
 
-fn main() {
-    part1();
-    part2();
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}", self.before, self.after)
+    }
 }
 
-fn part1() {
-    // part1 is now covered in part 2
+type Input = (PageRules, Vec<Vec<u64>>);
+
+/// All the rules, indexed so a section can be checked without going through every one of them.
+struct PageRules {
+    rules: Vec<Rule>,
+    // page -> the pages that have to come after it
+    afters: HashMap<u64, Vec<u64>>,
+    // (before, after) -> which rule says so
+    pairs: HashMap<(u64, u64), usize>,
 }
 
-fn part2() {
-    let (rules, sections) = read_input();
+impl PageRules {
+    fn new(rules: Vec<Rule>) -> PageRules {
+        let mut afters: HashMap<u64, Vec<u64>> = HashMap::new();
+        let mut pairs = HashMap::new();
+        for (idx, rule) in rules.iter().enumerate() {
+            afters.entry(rule.before).or_default().push(rule.after);
+            pairs.insert((rule.before, rule.after), idx);
+        }
+
+        PageRules { rules, afters, pairs }
+    }
+
+    fn rule(&self, before: u64, after: u64) -> Option<&Rule> {
+        self.pairs.get(&(before, after)).map(|&idx| &self.rules[idx])
+    }
+
+    /// Whether `section` follows every rule, in O(n): when there's a rule for each pair of
+    /// neighbours, as there is in the puzzle, the pages are in order exactly when every neighbour
+    /// comes after the one before it. That takes the rules at their word that they don't go in a
+    /// circle among the section's pages. Sections with a gap in the rules fall back to
+    /// `validate_by_position`.
+    fn validate(&self, section: &[u64]) -> bool {
+        let mut gap = false;
+        for pair in section.windows(2) {
+            match self.compare(pair[0], pair[1]) {
+                CmpOrdering::Less => {}
+                CmpOrdering::Greater => return false,
+                CmpOrdering::Equal => gap = true,
+            }
+        }
+
+        !gap || self.validate_by_position(section)
+    }
+
+    /// Checks every rule starting from a page in `section` against where the pages are: O(n +
+    /// those rules), including the ones about pages that aren't in the section.
+    fn validate_by_position(&self, section: &[u64]) -> bool {
+        let position: HashMap<u64, usize> = section.iter().enumerate().map(|(idx, &page)| (page, idx)).collect();
+        section.iter().enumerate().all(|(idx, page)| {
+            let afters = self.afters.get(page).map_or(&[][..], |afters| afters);
+            afters.iter().all(|after| position.get(after).is_none_or(|&after_idx| after_idx > idx))
+        })
+    }
+
+    fn compare(&self, left: u64, right: u64) -> CmpOrdering {
+        if self.pairs.contains_key(&(left, right)) {
+            CmpOrdering::Less
+        } else if self.pairs.contains_key(&(right, left)) {
+            CmpOrdering::Greater
+        } else {
+            CmpOrdering::Equal
+        }
+    }
+
+    /// `section` in an order that follows the rules. When there's a rule for every pair of pages
+    /// (and they don't go in a circle), that's just a sort; otherwise the graph works it out.
+    fn repair(&self, section: &[u64]) -> Result<Vec<u64>, Cycle> {
+        if self.is_total_order(section) {
+            let mut repaired = section.to_vec();
+            repaired.sort_by(|&left, &right| self.compare(left, right));
+            return Ok(repaired);
+        }
+
+        PageGraph::new(&self.rules, section).sort().map(|ordering| ordering.pages)
+    }
 
-    let mut sorted_score = 0;
-    let mut unsorted_score = 0;
-    for section in sections {
-        let ordered_pages = get_ordered_pages(&rules, &section);
-        let page_to_index: HashMap<&u64, usize> = pages_to_index_map(&ordered_pages);
-        
-        let mut sorted_section = section.clone();
-        sorted_section.sort_by_key(|page| page_to_index.get(page));
+    /// With a rule for every pair, the pages only line up if the first page comes before all
+    /// n-1 others, the second before n-2, and so on; anything else has a circle in it.
+    fn is_total_order(&self, section: &[u64]) -> bool {
+        let mut befores = vec![0; section.len()];
+        for (idx, &left) in section.iter().enumerate() {
+            for (other, &right) in section.iter().enumerate().skip(idx + 1) {
+                match self.compare(left, right) {
+                    CmpOrdering::Less => befores[idx] += 1,
+                    CmpOrdering::Greater => befores[other] += 1,
+                    CmpOrdering::Equal => return false,
+                }
+            }
+        }
 
-        let middle = sorted_section[sorted_section.len() / 2];
+        befores.sort_unstable();
+        befores.iter().enumerate().all(|(idx, &count)| idx == count)
+    }
 
-        match section.eq(&sorted_section) {
-            true => sorted_score += middle,
-            false => unsorted_score += middle
+    /// Every pair of pages in `section` that a rule says should be the other way around.
+    fn violations(&self, section: &[u64]) -> Vec<Violation<'_>> {
+        let mut violations = vec![];
+        for (idx, &earlier) in section.iter().enumerate() {
+            for &later in &section[idx + 1..] {
+                if let Some(rule) = self.rule(later, earlier) {
+                    violations.push(Violation { earlier, later, rule });
+                }
+            }
         }
+
+        violations
     }
+}
 
-    println!("Part 2: already sorted: {sorted_score} re-sorted: {unsorted_score}");
+struct Config {
+    // print what's wrong with each section to stderr
+    explain: bool,
 }
 
-fn pages_to_index_map(ordered_pages: &Vec<u64>) -> HashMap<&u64, usize> {
-    ordered_pages.iter()
-        .enumerate()
-        .map(|(idx, page)| (page, idx))
-        .collect()
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { explain: false };
+        for arg in args {
+            match arg.as_str() {
+                "--explain" => config.explain = true,
+                _ => return Err(format!("Unrecognized argument {arg:?}; d05 takes --explain")),
+            }
+        }
+
+        Ok(config)
+    }
 }
 
-fn get_ordered_pages(rules: &Vec<Rule>, section: &Vec<u64>) -> Vec<u64> {
-    // find all distinct page names
-    let mut unordered_pages: HashSet<u64> = section.iter().map(|i| i.clone()).collect();;
+fn main() {
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d05", &message));
 
-    // remove all rules that don't matter
-    let mut rules: Vec<&Rule> = rules.iter()
-        .filter(|rule| {
-            unordered_pages.contains(&rule.after) && unordered_pages.contains(&rule.before)
-        })
-        .collect();
-    
-    let mut ordered_pages: Vec<u64> = vec![];
-    while !unordered_pages.is_empty() {
-        // figure out which pages come after something else
-        let right_pages: HashSet<u64> = rules.iter()
-            .map(|rule| rule.after.clone())
-            .collect();
-
-        // figure out which page doesn't come after any remaining pages
-        let nothing_before: HashSet<_> = unordered_pages.difference(&right_pages).collect();
-        if nothing_before.len() != 1 {
-            panic!("I fucked up! The pages with nothing before them are {:?}", nothing_before);
+    runner::run("d05", read_input, part1, |input| part2(input, &config));
+}
+
+fn part1((rules, sections): &Input) -> u64 {
+    sections.iter()
+        .filter(|section| rules.validate(section))
+        .map(|section| middle(section))
+        .sum()
+}
+
+fn part2((rules, sections): &Input, config: &Config) -> u64 {
+    let mut score = 0;
+    for (idx, section) in sections.iter().enumerate() {
+        if rules.validate(section) {
+            continue;
+        }
+        if config.explain {
+            explain(rules, idx, section);
         }
-        let next_page = nothing_before.into_iter().next().unwrap().clone();
 
-        // remove the page from the list
-        unordered_pages.remove(&next_page);
-        // remove any rules that say things must come after this page; we know that!
-        rules = rules.into_iter().filter(|rule| rule.before != next_page).collect();
-        // add this page to the ordered list
-        ordered_pages.push(next_page);
+        match rules.repair(section) {
+            Ok(repaired) => score += middle(&repaired),
+            Err(cycle) => eprintln!("d05: section {} can't be sorted: {cycle}", idx + 1),
+        }
     }
 
-    ordered_pages
+    score
+}
+
+fn middle(section: &[u64]) -> u64 {
+    section[section.len() / 2]
 }
 
-fn read_input() -> (Vec<Rule>, Vec<Vec<u64>>) {
-    let file_str = fs::read_to_string("d05/input").unwrap();
-    let mut parts = file_str.split("\n\n");
+fn explain(rules: &PageRules, idx: usize, section: &[u64]) {
+    let violations = rules.violations(section);
+    let unique = match PageGraph::new(&rules.rules, section).sort() {
+        Ok(ordering) if ordering.unique => "only one order",
+        Ok(_) => "more than one order",
+        Err(_) => "no order",
+    };
+    eprintln!("Section {} has {unique} and {} pairs out of order", idx + 1, violations.len());
+    for violation in violations.iter() {
+        eprintln!("  {} is before {}, breaking {}", violation.earlier, violation.later, violation.rule);
+    }
+}
 
-    let rules: Vec<Rule> = parts
-        .next()
-        .unwrap()
+fn read_input(input: &str) -> ParseResult<Input> {
+    let source = Source::new(input);
+    let [rules, sections] = source.sections_exact(input)?;
+
+    let rules: Vec<Rule> = rules
         .lines()
         .map(|line| {
-            let parts: Vec<&str> = line.split("|").collect();
-            Rule {
-                before: parts[0].parse().unwrap(),
-                after: parts[1].parse().unwrap(),
-            }
+            let [before, after] = source.split_exact(line, "|")?;
+            Ok(Rule {
+                before: source.parse(before, "a page number")?,
+                after: source.parse(after, "a page number")?,
+            })
         })
-        .collect();
+        .collect::<ParseResult<_>>()?;
 
     // parse the sections
-    let sections: Vec<Vec<u64>> = parts
-        .next()
-        .unwrap()
+    let sections: Vec<Vec<u64>> = sections
         .lines()
-        .map(|line| {
-            line.split(",")
-                .map(|num| num.parse().unwrap())
-                .collect()
-        })
-        .collect();
+        .map(|line| read_section(&source, line))
+        .collect::<ParseResult<_>>()?;
+
+    Ok((PageRules::new(rules), sections))
+}
+
+/// A page can only be in a section once; there's no saying where a second copy would go.
+fn read_section(source: &Source, line: &str) -> ParseResult<Vec<u64>> {
+    let mut pages = vec![];
+    for word in line.split(',').map(str::trim) {
+        let page = source.parse(word, "a page number")?;
+        if pages.contains(&page) {
+            return Err(source.error(word, "a page that isn't already in the section"));
+        }
+        pages.push(page);
+    }
+
+    Ok(pages)
+}
 
-    (rules, sections)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Ordering;
+
+    const EXAMPLE: &str = "47|53\n97|13\n97|61\n97|47\n75|29\n61|13\n75|53\n29|13\n97|29\n53|29\n61|53\n\
+                           97|53\n61|29\n47|13\n75|47\n97|75\n47|61\n75|61\n47|29\n75|13\n53|13\n\n\
+                           75,47,61,53,29\n97,61,53,29,13\n75,29,13\n75,97,47,61,53\n61,13,29\n97,13,75,29,47\n";
+
+    fn rule(before: u64, after: u64) -> Rule {
+        Rule { before, after }
+    }
+
+    #[test]
+    fn example() {
+        let input = read_input(EXAMPLE).unwrap();
+        assert_eq!(part1(&input), 143);
+        assert_eq!(part2(&input, &Config { explain: false }), 123);
+    }
+
+    #[test]
+    fn validates_and_repairs() {
+        let (rules, sections) = read_input(EXAMPLE).unwrap();
+        let valid: Vec<bool> = sections.iter().map(|section| rules.validate(section)).collect();
+        assert_eq!(valid, [true, true, true, false, false, false]);
+        assert_eq!(rules.repair(&sections[5]), Ok(vec![97, 75, 47, 29, 13]));
+
+        // not every pair has a rule, so the graph sorts this one
+        let rules = PageRules::new(vec![rule(1, 3), rule(3, 2)]);
+        assert!(!rules.validate(&[2, 1, 3]));
+        // nothing between 1 and 2, but 3 still has to go before 2
+        assert!(!rules.validate(&[1, 2, 3]));
+        assert!(rules.validate(&[1, 3, 2]));
+        assert_eq!(rules.repair(&[2, 1, 3]), Ok(vec![1, 3, 2]));
+        // a rule for every pair, but in a circle
+        let rules = PageRules::new(vec![rule(1, 2), rule(2, 3), rule(3, 1)]);
+        assert!(rules.repair(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn rejects_repeated_pages() {
+        let err = read_input("1|2\n\n1,2,3\n3,2,3\n").err().unwrap();
+        assert_eq!(err.expected, "a page that isn't already in the section");
+        assert_eq!((err.line, err.column, err.found.as_deref()), (4, 5, Some("3")));
+    }
+
+    #[test]
+    fn orderings() {
+        let (rules, _) = read_input(EXAMPLE).unwrap();
+        let ordering = PageGraph::new(&rules.rules, &[61, 13, 29]).sort().unwrap();
+        assert_eq!(ordering, Ordering { pages: vec![61, 29, 13], unique: true });
+
+        // nothing says which of these goes first
+        let rules = [rule(1, 3), rule(2, 3)];
+        assert!(!PageGraph::new(&rules, &[3, 2, 1]).sort().unwrap().unique);
+    }
+
+    #[test]
+    fn cycles() {
+        let rules = [rule(1, 2), rule(5, 1), rule(2, 3), rule(3, 1), rule(3, 4)];
+        let cycle = PageGraph::new(&rules, &[1, 2, 3, 4, 5]).sort().unwrap_err();
+        assert_eq!(cycle, Cycle { rules: vec![rule(1, 2), rule(2, 3), rule(3, 1)] });
+        assert_eq!(cycle.to_string(), "the rules 1|2, 2|3, 3|1 go in a circle");
+
+        // rules about pages that aren't in the section don't count
+        assert!(PageGraph::new(&rules, &[1, 2, 3]).sort().is_err());
+        assert!(PageGraph::new(&rules, &[1, 2, 4]).sort().is_ok());
+    }
+
+    #[test]
+    fn names_violated_rules() {
+        let (rules, _) = read_input(EXAMPLE).unwrap();
+        let violations = rules.violations(&[61, 13, 29]);
+        assert_eq!(violations, vec![Violation { earlier: 13, later: 29, rule: &rule(29, 13) }]);
+        assert!(rules.violations(&[75, 47, 61, 53, 29]).is_empty());
+    }
 }
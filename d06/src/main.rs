@@ -1,89 +1,421 @@
+mod render;
+
 use crate::Tile::{Blocked, Free};
 use lib2d::Direction::{Down, Left, Right, Up};
-use lib2d::{dir_delta, Direction, Point2d};
+use lib2d::{dir_delta, dir_opposite, Direction, Grid, Point2d};
+use libparse::{ParseResult, Source};
+use rayon::prelude::*;
+use std::borrow::Cow;
 use std::cmp::PartialEq;
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
 
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 enum Tile {
     Free,
     Blocked
 }
 
+type Map = Grid<Tile>;
+
+type Lab = (Vec<Guard>, Map);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Guard {
+    pos: Point2d<i32>,
+    dir: Direction,
+    // how many times they've turned so far, which matters when they don't always turn the same way
+    turns: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Turn {
+    Clockwise,
+    Counterclockwise,
+    Around,
+}
+
+/// How a guard turns when they bump into something: their nth bump uses the nth turn in the
+/// sequence (wrapping around), so a single turn means they always turn the same way.
+#[derive(Clone, Debug, PartialEq)]
+struct TurnPolicy {
+    sequence: Vec<Turn>,
+}
+
+impl TurnPolicy {
+    fn always(turn: Turn) -> TurnPolicy {
+        TurnPolicy { sequence: vec![turn] }
+    }
+
+    /// `right`, `left`, `reverse`, or several of them separated by commas to take turns
+    fn parse(spec: &str) -> Result<TurnPolicy, String> {
+        let sequence = spec.split(",")
+            .map(|turn| match turn {
+                "right" => Ok(Turn::Clockwise),
+                "left" => Ok(Turn::Counterclockwise),
+                "reverse" => Ok(Turn::Around),
+                _ => Err(format!("Unknown turn {turn:?}; expected right, left, or reverse")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TurnPolicy { sequence })
+    }
+
+    /// After this many turns, the policy starts over
+    fn period(&self) -> usize {
+        self.sequence.len()
+    }
+
+    fn turn(&self, guard: Guard) -> Guard {
+        let dir = match self.sequence[guard.turns % self.period()] {
+            Turn::Clockwise => turn_right(guard.dir),
+            Turn::Counterclockwise => turn_left(guard.dir),
+            Turn::Around => dir_opposite(guard.dir),
+        };
+        Guard { dir, turns: guard.turns + 1, ..guard }
+    }
+}
+
+struct Config {
+    policy: TurnPolicy,
+    // print what happened to each guard to stderr
+    report: bool,
+    // print how often each tile got visited to stderr
+    heatmap: bool,
+    // draw each guard's path to stderr
+    render: bool,
+    // ...or to a PPM image
+    render_to: Option<PathBuf>,
+    // an extra obstacle to try out, drawn as 'O'
+    obstacle: Option<Point2d<i32>>,
+}
+
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config {
+            policy: TurnPolicy::always(Turn::Clockwise),
+            report: false,
+            heatmap: false,
+            render: false,
+            render_to: None,
+            obstacle: None,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--policy" => {
+                    let spec = args.next().ok_or("--policy needs a turn policy, like right or left,left,reverse")?;
+                    config.policy = TurnPolicy::parse(&spec)?;
+                }
+                "--report" => config.report = true,
+                "--heatmap" => config.heatmap = true,
+                "--render" => config.render = true,
+                "--render-to" => {
+                    let path = args.next().ok_or("--render-to needs a file to write the image to")?;
+                    config.render_to = Some(PathBuf::from(path));
+                }
+                "--obstacle" => {
+                    let spec = args.next().ok_or("--obstacle needs a tile, like 3,6")?;
+                    config.obstacle = Some(parse_point(&spec)?);
+                }
+                _ => return Err(format!(
+                    "Unrecognized argument {arg:?}; d06 takes --policy, --report, --heatmap, --render, --render-to, and --obstacle"
+                )),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The map with our extra obstacle on it, if we've got one. Whether there's room for it
+    /// depends on the map, so it can only be checked once that's loaded.
+    fn map<'a>(&self, (guards, map): &'a Lab) -> Result<Cow<'a, Map>, String> {
+        let Some(obstacle) = self.obstacle else {
+            return Ok(Cow::Borrowed(map));
+        };
+        if map.get(obstacle) != Some(&Free) || guards.iter().any(|guard| guard.pos == obstacle) {
+            return Err(format!("--obstacle {},{} isn't an empty tile", obstacle.x, obstacle.y));
+        }
+
+        let mut map = map.clone();
+        map[obstacle] = Blocked;
+        Ok(Cow::Owned(map))
+    }
+}
+
+fn parse_point(spec: &str) -> Result<Point2d<i32>, String> {
+    let bad_point = || format!("Expected a tile like 3,6, not {spec:?}");
+    let (x, y) = spec.split_once(',').ok_or_else(bad_point)?;
+    let x = x.trim().parse().map_err(|_| bad_point())?;
+    let y = y.trim().parse().map_err(|_| bad_point())?;
+    Ok(Point2d::new(x, y))
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Exited,
+    /// `steps[cycle_start..]` repeats forever
+    Looped { cycle_start: usize },
+}
+
+struct Patrol {
+    start: Guard,
+    /// Every state the guard was in, in order: one per step or turn
+    steps: Vec<Guard>,
+    outcome: Outcome,
+}
+
+impl Patrol {
+    /// Where the guard walked off the map, or where their loop starts
+    fn last_seen(&self) -> Guard {
+        match self.outcome {
+            Outcome::Exited => *self.steps.last().unwrap(),
+            Outcome::Looped { cycle_start } => self.steps[cycle_start],
+        }
+    }
+
+    /// Every time the guard stepped onto a tile (turning in place doesn't count)
+    fn add_visits(&self, counts: &mut Grid<u32>) {
+        let mut last_pos = None;
+        for step in self.steps.iter() {
+            if last_pos != Some(step.pos) {
+                counts[step.pos] += 1;
+            }
+            last_pos = Some(step.pos);
+        }
+    }
+
+    /// Every tile the guard steps on (other than the start), along with the guard as they were
+    /// just before they first stepped onto it.
+    fn first_visits(&self, map: &Map) -> Vec<(Point2d<i32>, Guard)> {
+        let mut visited = Grid::new(map.width(), map.height(), false);
+        visited[self.start.pos] = true;
+
+        self.steps.windows(2)
+            .filter_map(|pair| {
+                let (before, after) = (pair[0], pair[1]);
+                if visited[after.pos] {
+                    return None;
+                }
+                visited[after.pos] = true;
+                Some((after.pos, before))
+            })
+            .collect()
+    }
+}
+
 fn main() {
-    part1();
-    part2();
-}
-
-fn part1() {
-    let (start_point, map) = load_map();
-    let (looped, visited_points) = does_maze_loop(&start_point, &map, None);
-    if looped {
-        panic!("Part 1 looped, it shouldn't do that!");
-    }
-    let distinct_points: HashSet<_> = visited_points.into_iter()
-        .map(|(point, _)| point)
-        .collect();
-    println!("Part 1: {:?}", distinct_points.len());
-}
-
-fn part2() {
-    let (start_point, map) = load_map();
-    let (_, vanilla_points) = does_maze_loop(&start_point, &map, None);
-    
-    // find just the points where we travel with no extra obstacles
-    let vanilla_points: HashSet<_> = vanilla_points.into_iter()
-        .map(|(point, _dir)| point)
-        .collect();
-
-    // only consider inserting an obstacle along the original path; other points won't do anything
-    let loops = vanilla_points.iter()
-        .filter(|point| {
-            let (loops, _) = does_maze_loop(&start_point, &map, Some(*point));
-            loops
-        })
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d06", &message));
+
+    runner::run("d06", load_map, |lab| part1(lab, &config), |lab| part2(lab, &config));
+}
+
+fn part1(lab: &Lab, config: &Config) -> Option<usize> {
+    let (guards, _) = lab;
+    let map = &*with_obstacle(lab, config)?;
+    let mut counts = Grid::new(map.width(), map.height(), 0);
+    let mut patrols = vec![];
+    for (idx, guard) in guards.iter().enumerate() {
+        let patrol = patrol(map, *guard, &config.policy);
+        patrol.add_visits(&mut counts);
+
+        if config.report {
+            let last_seen = patrol.last_seen();
+            let (x, y) = (last_seen.pos.x, last_seen.pos.y);
+            match patrol.outcome {
+                Outcome::Exited => eprintln!("Guard {idx} exited from {x},{y} heading {:?}", last_seen.dir),
+                Outcome::Looped { cycle_start } => eprintln!(
+                    "Guard {idx} looped from {x},{y} heading {:?}, every {} moves after the first {cycle_start}",
+                    last_seen.dir,
+                    patrol.steps.len() - cycle_start
+                ),
+            }
+        }
+        patrols.push(patrol);
+    }
+
+    if config.heatmap {
+        eprint!("{}", heatmap(map, &counts));
+    }
+    if config.render || config.render_to.is_some() {
+        let marks = render::marks(map, &patrols, config.obstacle);
+        if config.render {
+            eprint!("{}", render::to_text(&marks, std::io::stderr().is_terminal()));
+        }
+        if let Some(path) = &config.render_to {
+            if let Err(err) = std::fs::write(path, render::to_ppm(&marks)) {
+                eprintln!("d06: couldn't write {}: {err}", path.display());
+            }
+        }
+    }
+
+    let visited = counts.iter()
+        .filter(|(_, count)| **count > 0)
         .count();
+    Some(visited)
+}
 
-    println!("Part 2: {loops}");
+fn part2(lab: &Lab, config: &Config) -> Option<usize> {
+    let (guards, _) = lab;
+    let map = &*with_obstacle(lab, config)?;
+    let jumps = JumpTable::new(map);
+    // this one's all about the first guard
+    let patrol = patrol(map, guards[0], &config.policy);
+
+    // only consider inserting an obstacle along the original path; other points won't do anything.
+    // Everything up until the guard first reaches the obstacle plays out the same as before, so
+    // start from right in front of it.
+    let loops = patrol.first_visits(map)
+        .par_iter()
+        .filter(|(obstacle, guard)| jumps.loops_with(map, *guard, *obstacle, &config.policy))
+        .count();
+    Some(loops)
 }
 
-fn does_maze_loop(start_point: &Point2d<i32>,
-                  map: &HashMap<Point2d<i32>, Tile>,
-                  introduced_obstacle: Option<&Point2d<i32>>) -> (bool, HashSet<(Point2d<i32>, Direction)>) {
-    let mut cur_pos = start_point.clone();
-    let mut cur_dir = Up;
-    let mut visited_points: HashSet<(Point2d<i32>, Direction)> = HashSet::new();
-    visited_points.insert((cur_pos.clone(), cur_dir));
+/// `config.map`, or nothing (after saying why) when the extra obstacle doesn't fit.
+fn with_obstacle<'a>(lab: &'a Lab, config: &Config) -> Option<Cow<'a, Map>> {
+    match config.map(lab) {
+        Ok(map) => Some(map),
+        Err(message) => {
+            eprintln!("d06: {message}");
+            None
+        }
+    }
+}
+
+/// Walks the guard one tile at a time until they leave the map or start going in circles.
+fn patrol(map: &Map, start: Guard, policy: &TurnPolicy) -> Patrol {
+    // (position, direction, where they are in the turn policy) -> when we first saw it
+    let mut seen: HashMap<(Point2d<i32>, Direction, usize), usize> = HashMap::new();
+    let mut steps = vec![];
+
+    let mut guard = start;
     loop {
-        let new_point = cur_pos + dir_delta(cur_dir);
+        let state = (guard.pos, guard.dir, guard.turns % policy.period());
+        if let Some(&cycle_start) = seen.get(&state) {
+            return Patrol { start, steps, outcome: Outcome::Looped { cycle_start } };
+        }
+        seen.insert(state, steps.len());
+        steps.push(guard);
 
-        if visited_points.contains(&(new_point, cur_dir)) {
-            return (true, visited_points);
+        let new_point = guard.pos + dir_delta(guard.dir);
+        match map.get(new_point) {
+            None => return Patrol { start, steps, outcome: Outcome::Exited },
+            Some(Free) => guard.pos = new_point,
+            Some(Blocked) => guard = policy.turn(guard),
         }
+    }
+}
 
-        let destination_tile = if Some(&new_point) == introduced_obstacle {
-            Some(&Blocked)
-        } else {
-            map.get(&new_point)
+/// One character per tile: obstacles are '#', then '.' for never visited, 1-9 for how many
+/// times, and '*' for more than that.
+fn heatmap(map: &Map, counts: &Grid<u32>) -> String {
+    let mut heatmap = String::new();
+    for (point, count) in counts.iter() {
+        let char = match (map[point], count) {
+            (Blocked, _) => '#',
+            (Free, 0) => '.',
+            (Free, 1..=9) => char::from_digit(*count, 10).unwrap(),
+            (Free, _) => '*',
         };
+        heatmap.push(char);
+        if point.x as usize == map.width() - 1 {
+            heatmap.push('\n');
+        }
+    }
+
+    heatmap
+}
+
+/// For every tile and direction: where the guard stops (facing an obstacle) if they set off that
+/// way, or None if they walk off the map. Lets the patrol go obstacle to obstacle.
+struct JumpTable {
+    stops: [Vec<Option<u32>>; 4],
+}
 
-        match destination_tile {
-            None => {
-                return (false, visited_points)
-            },
-            Some(Free) => {
-                cur_pos = new_point;
-                visited_points.insert((cur_pos.clone(), cur_dir));
-            },
-            Some(Blocked) => {
-                cur_dir = turn_right(cur_dir);
+impl JumpTable {
+    fn new(map: &Map) -> JumpTable {
+        let mut stops: [Vec<Option<u32>>; 4] = Default::default();
+        for dir in [Up, Right, Down, Left] {
+            // fill in the tile ahead before the tile behind it
+            let indexes: Vec<usize> = match dir {
+                Up | Left => (0..map.len()).collect(),
+                Down | Right => (0..map.len()).rev().collect(),
+            };
+
+            let table = &mut stops[dir_slot(dir)];
+            table.resize(map.len(), None);
+            for idx in indexes {
+                let ahead = map.point_at(idx) + dir_delta(dir);
+                table[idx] = match map.index_of(ahead) {
+                    None => None,
+                    Some(ahead_idx) if map[ahead_idx] == Blocked => Some(idx as u32),
+                    Some(ahead_idx) => table[ahead_idx],
+                };
             }
         }
+
+        JumpTable { stops }
+    }
+
+    /// Where the guard stops next, with one extra obstacle on the map.
+    fn stop(&self, map: &Map, guard: Guard, obstacle: Point2d<i32>) -> Option<Point2d<i32>> {
+        let delta = dir_delta(guard.dir);
+        let distance = |point: Point2d<i32>| {
+            let offset = point - guard.pos;
+            offset.x * delta.x + offset.y * delta.y
+        };
+
+        let idx = map.index_of(guard.pos).unwrap();
+        let stop = self.stops[dir_slot(guard.dir)][idx].map(|idx| map.point_at(idx as usize));
+
+        // the extra obstacle only matters if it's dead ahead, and closer than the real one
+        let offset = obstacle - guard.pos;
+        let in_line = offset.x * delta.y == 0 && offset.y * delta.x == 0;
+        let obstacle_distance = distance(obstacle);
+        if in_line && obstacle_distance > 0 && stop.is_none_or(|stop| obstacle_distance <= distance(stop)) {
+            return Some(obstacle - delta);
+        }
+
+        stop
+    }
+
+    fn loops_with(&self, map: &Map, mut guard: Guard, obstacle: Point2d<i32>, policy: &TurnPolicy) -> bool {
+        // which ways the guard has been facing when they stopped at each tile, at each point in
+        // their turn policy
+        let mut stopped = vec![0_u8; map.len() * policy.period()];
+        while let Some(stop) = self.stop(map, guard, obstacle) {
+            let idx = map.index_of(stop).unwrap() * policy.period() + guard.turns % policy.period();
+            let bit = dir_bit(guard.dir);
+            if stopped[idx] & bit != 0 {
+                return true;
+            }
+            stopped[idx] |= bit;
+
+            guard = policy.turn(Guard { pos: stop, ..guard });
+        }
+
+        false
     }
 }
 
+fn dir_slot(direction: Direction) -> usize {
+    match direction {
+        Up => 0,
+        Right => 1,
+        Down => 2,
+        Left => 3
+    }
+}
+
+fn dir_bit(direction: Direction) -> u8 {
+    1 << dir_slot(direction)
+}
+
 fn turn_right(current_direction: Direction) -> Direction {
     match current_direction {
         Up => Right,
@@ -93,27 +425,158 @@ fn turn_right(current_direction: Direction) -> Direction {
     }
 }
 
-fn load_map() -> (Point2d<i32>, HashMap<Point2d<i32>, Tile>) {
-    let mut start_pos: Option<Point2d<i32>> = None;
-    let mut map = HashMap::new();
-    fs::read_to_string("d06/input").unwrap()
-        .lines().enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate()
-                .for_each(|(x, char)| {
-                    let point = Point2d::new(x as i32, y as i32);
-                    let tile = match char {
-                        '.' => Free,
-                        '#' => Blocked,
-                        '^' => {
-                            start_pos = Some(point.clone());
-                            Free
-                        },
-                        _ => panic!("Unrecognized tile: {:?}", char),
-                    };
-                    map.insert(point,  tile);
-                })
-        });
-
-    (start_pos.unwrap(), map)
-}
\ No newline at end of file
+fn turn_left(current_direction: Direction) -> Direction {
+    match current_direction {
+        Up => Left,
+        Left => Down,
+        Down => Right,
+        Right => Up
+    }
+}
+
+fn load_map(input: &str) -> ParseResult<Lab> {
+    let source = Source::new(input);
+    let mut guards = vec![];
+    let mut rows = vec![];
+    for (y, line) in input.lines().enumerate() {
+        let mut row = vec![];
+        for (x, (idx, char)) in line.char_indices().enumerate() {
+            let pos = Point2d::new(x as i32, y as i32);
+            let guard_dir = match char {
+                '^' => Some(Up),
+                'v' => Some(Down),
+                '<' => Some(Left),
+                '>' => Some(Right),
+                _ => None,
+            };
+            let tile = match (char, guard_dir) {
+                ('.', _) => Free,
+                ('#', _) => Blocked,
+                (_, Some(dir)) => {
+                    guards.push(Guard { pos, dir, turns: 0 });
+                    Free
+                },
+                _ => return Err(source.error(&line[idx..idx + char.len_utf8()], "a tile ('.' or '#') or a guard (^, v, <, or >)")),
+            };
+            row.push(tile);
+        }
+        if rows.first().is_some_and(|first: &Vec<Tile>| first.len() != row.len()) {
+            return Err(source.error(line, format!("a row {} tiles wide", rows[0].len())));
+        }
+        rows.push(row);
+    }
+
+    if guards.is_empty() {
+        return Err(source.missing(input, "a guard (^, v, <, or >)"));
+    }
+    Ok((guards, Grid::from_rows(rows).unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....\n.........#\n..........\n..#.......\n.......#..\n\
+                           ..........\n.#..^.....\n........#.\n#.........\n......#...\n";
+
+    fn config(policy: &str) -> Config {
+        Config::from_args(vec![String::from("--policy"), String::from(policy)]).unwrap()
+    }
+
+    #[test]
+    fn example() {
+        let lab = load_map(EXAMPLE).unwrap();
+        assert_eq!(part1(&lab, &config("right")), Some(41));
+        assert_eq!(part2(&lab, &config("right")), Some(6));
+    }
+
+    #[test]
+    fn jumps_stop_in_front_of_obstacles() {
+        let (guards, map) = load_map(EXAMPLE).unwrap();
+        let jumps = JumpTable::new(&map);
+        let guard = guards[0];
+        // nowhere near the guard's path
+        let far_away = Point2d::new(9, 9);
+
+        assert_eq!(jumps.stop(&map, guard, far_away), Some(Point2d::new(4, 1)));
+        assert_eq!(jumps.stop(&map, guard, Point2d::new(4, 3)), Some(Point2d::new(4, 4)));
+        assert_eq!(jumps.stop(&map, Guard { dir: Down, ..guard }, far_away), None);
+    }
+
+    #[test]
+    fn several_guards() {
+        let (guards, map) = load_map("#.<#\n.v..\n").unwrap();
+        assert_eq!(guards.len(), 2);
+
+        // turning back and forth, the first one bounces between the walls forever
+        let first = patrol(&map, guards[0], &TurnPolicy::always(Turn::Around));
+        assert_eq!(first.outcome, Outcome::Looped { cycle_start: 0 });
+        let second = patrol(&map, guards[1], &TurnPolicy::always(Turn::Around));
+        assert_eq!(second.outcome, Outcome::Exited);
+        assert_eq!(second.last_seen().pos, Point2d::new(1, 1));
+    }
+
+    #[test]
+    fn turn_policies_agree_with_jumps() {
+        let (guards, map) = load_map(EXAMPLE).unwrap();
+        let jumps = JumpTable::new(&map);
+        for spec in ["right", "left", "reverse", "right,right,left", "left,reverse"] {
+            let policy = TurnPolicy::parse(spec).unwrap();
+
+            // re-check each obstacle with the slow walk
+            for (obstacle, before) in patrol(&map, guards[0], &policy).first_visits(&map) {
+                let mut blocked = map.clone();
+                blocked[obstacle] = Blocked;
+                let slow = patrol(&blocked, before, &policy);
+                let fast = jumps.loops_with(&map, before, obstacle, &policy);
+                assert_eq!(fast, slow.outcome != Outcome::Exited, "{spec} with an obstacle at {obstacle:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn heatmaps() {
+        let (guards, map) = load_map("#..\n.^.\n..#\n").unwrap();
+        let mut counts = Grid::new(3, 3, 0);
+        patrol(&map, guards[0], &TurnPolicy::always(Turn::Clockwise)).add_visits(&mut counts);
+        assert_eq!(heatmap(&map, &counts), "#1.\n.1.\n..#\n");
+        assert!(TurnPolicy::parse("sideways").is_err());
+    }
+
+    #[test]
+    fn renders_paths() {
+        let lab = load_map(EXAMPLE).unwrap();
+        let config = Config::from_args(vec![String::from("--obstacle"), String::from("3,6")]).unwrap();
+        let map = config.map(&lab).unwrap();
+        let patrol = patrol(&map, lab.0[0], &config.policy);
+        assert_eq!(patrol.outcome, Outcome::Looped { cycle_start: 0 });
+
+        let marks = render::marks(&map, &[patrol], config.obstacle);
+        let expected = "....#.....\n....+---+#\n....|...|.\n..#.|...|.\n....|..#|.\n\
+                        ....|...|.\n.#.O^---+.\n........#.\n#.........\n......#...\n";
+        assert_eq!(render::to_text(&marks, false), expected);
+        assert!(matches!(marks[Point2d::new(4, 1)], render::Mark::Path { cycle: true, .. }));
+
+        let ppm = render::to_ppm(&marks);
+        assert!(ppm.starts_with(b"P6\n50 50\n255\n"));
+        assert_eq!(ppm.len(), "P6\n50 50\n255\n".len() + 50 * 50 * 3);
+
+        // right on top of the guard
+        let config = Config::from_args(vec![String::from("--obstacle"), String::from("4,6")]).unwrap();
+        assert_eq!(config.map(&lab).err().unwrap(), "--obstacle 4,6 isn't an empty tile");
+        assert_eq!(part1(&lab, &config), None);
+    }
+
+    #[test]
+    fn lead_ins_are_not_cycles() {
+        let (guards, map) = load_map(".#...\n....#\n#....\n...#.\n.^...\n").unwrap();
+        let patrol = patrol(&map, guards[0], &TurnPolicy::always(Turn::Clockwise));
+        assert_eq!(patrol.outcome, Outcome::Looped { cycle_start: 2 });
+
+        let marks = render::marks(&map, &[patrol], None);
+        assert_eq!(render::to_text(&marks, false), ".#...\n.+-+#\n#+-+.\n.|.#.\n.^...\n");
+        assert!(matches!(marks[Point2d::new(1, 3)], render::Mark::Path { cycle: false, .. }));
+        assert!(matches!(marks[Point2d::new(1, 2)], render::Mark::Path { cycle: true, .. }));
+        assert!(render::to_text(&marks, true).contains("\x1b[36m|"));
+    }
+}
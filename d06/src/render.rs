@@ -0,0 +1,133 @@
+use crate::Tile::Blocked;
+use crate::{Map, Outcome, Patrol};
+use lib2d::Direction::{Down, Left, Right, Up};
+use lib2d::{Direction, Grid, Point2d};
+
+/// What ends up drawn on each tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mark {
+    Empty,
+    Wall,
+    /// The extra obstacle we're trying out
+    Obstacle,
+    Start(Direction),
+    Path { vertical: bool, horizontal: bool, cycle: bool },
+}
+
+impl Mark {
+    fn glyph(&self) -> char {
+        match self {
+            Mark::Empty => '.',
+            Mark::Wall => '#',
+            Mark::Obstacle => 'O',
+            Mark::Start(Up) => '^',
+            Mark::Start(Down) => 'v',
+            Mark::Start(Left) => '<',
+            Mark::Start(Right) => '>',
+            Mark::Path { vertical: true, horizontal: true, .. } => '+',
+            Mark::Path { vertical: true, .. } => '|',
+            Mark::Path { .. } => '-',
+        }
+    }
+
+    /// ANSI color, if it's worth highlighting
+    fn color(&self) -> Option<&'static str> {
+        match self {
+            Mark::Obstacle => Some("\x1b[1;33m"),
+            Mark::Start(_) => Some("\x1b[1;32m"),
+            Mark::Path { cycle: true, .. } => Some("\x1b[1;31m"),
+            Mark::Path { cycle: false, .. } => Some("\x1b[36m"),
+            _ => None,
+        }
+    }
+
+    fn rgb(&self) -> [u8; 3] {
+        match self {
+            Mark::Empty => [16, 16, 24],
+            Mark::Wall => [110, 110, 120],
+            Mark::Obstacle => [255, 210, 0],
+            Mark::Start(_) => [40, 220, 60],
+            Mark::Path { cycle: true, .. } => [235, 50, 50],
+            Mark::Path { cycle: false, .. } => [60, 170, 230],
+        }
+    }
+}
+
+/// Lays every guard's path over the map. Tiles they crossed both ways (or turned on) get a `+`,
+/// and anything that's part of a loop is marked as such.
+pub fn marks(map: &Map, patrols: &[Patrol], obstacle: Option<Point2d<i32>>) -> Grid<Mark> {
+    let mut marks = map.map(|tile| if *tile == Blocked { Mark::Wall } else { Mark::Empty });
+
+    for patrol in patrols {
+        let cycle_start = match patrol.outcome {
+            Outcome::Looped { cycle_start } => cycle_start,
+            Outcome::Exited => patrol.steps.len(),
+        };
+
+        for (idx, step) in patrol.steps.iter().enumerate() {
+            let vertical = matches!(step.dir, Up | Down);
+            let cycle = idx >= cycle_start;
+            marks[step.pos] = match marks[step.pos] {
+                Mark::Path { vertical: v, horizontal: h, cycle: c } => Mark::Path {
+                    vertical: v || vertical,
+                    horizontal: h || !vertical,
+                    cycle: c || cycle,
+                },
+                _ => Mark::Path { vertical, horizontal: !vertical, cycle },
+            };
+        }
+    }
+
+    // starts go on top of any paths that wander back over them
+    for patrol in patrols {
+        marks[patrol.start.pos] = Mark::Start(patrol.start.dir);
+    }
+    if let Some(obstacle) = obstacle {
+        marks[obstacle] = Mark::Obstacle;
+    }
+
+    marks
+}
+
+/// One line per row; with `color`, the loop is red and the lead-in is cyan.
+pub fn to_text(marks: &Grid<Mark>, color: bool) -> String {
+    let mut text = String::new();
+    for (point, mark) in marks.iter() {
+        match mark.color().filter(|_| color) {
+            Some(code) => text.push_str(&format!("{code}{}\x1b[0m", mark.glyph())),
+            None => text.push(mark.glyph()),
+        }
+        if point.x as usize == marks.width() - 1 {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+const CELL: usize = 5;
+
+/// A binary PPM, with each tile drawn as a little square and the path glyphs drawn as lines
+/// through the middle of it.
+pub fn to_ppm(marks: &Grid<Mark>) -> Vec<u8> {
+    let (width, height) = (marks.width() * CELL, marks.height() * CELL);
+    let mut ppm = format!("P6\n{width} {height}\n255\n").into_bytes();
+
+    for y in 0..height {
+        for x in 0..width {
+            let mark = marks[Point2d::new((x / CELL) as i32, (y / CELL) as i32)];
+            let (cx, cy) = (x % CELL, y % CELL);
+            let middle = CELL / 2;
+
+            let lit = match mark {
+                Mark::Path { vertical, horizontal, .. } => (vertical && cx == middle) || (horizontal && cy == middle),
+                Mark::Empty => false,
+                _ => true,
+            };
+            let rgb = if lit { mark.rgb() } else { Mark::Empty.rgb() };
+            ppm.extend_from_slice(&rgb);
+        }
+    }
+
+    ppm
+}
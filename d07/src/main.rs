@@ -1,74 +1,428 @@
-use std::fs;
+use libparse::{ParseResult, Source};
 
 struct Equation {
     result: u64,
     values: Vec<u64>
 }
 
-fn main() {
-    part1();
-    part2();
+/// Something that can go between two values. Everything's checked: overflowing just means that
+/// operator doesn't work there.
+trait Operator {
+    fn symbol(&self) -> &'static str;
+
+    /// How tightly it binds when evaluating with precedence; higher goes first.
+    fn precedence(&self) -> u8;
+
+    fn apply(&self, left: u64, right: u64) -> Option<u64>;
+
+    /// What `left` has to be so that `left <op> right == result`.
+    fn undo(&self, result: u64, right: u64) -> Undo;
 }
 
-fn part1() {
-    let equations = load_equations();
-    let sum: u64 = equations.iter()
-        .filter(|equation| {
-            let possible_results = get_possible_results(&equation.values, false);
-            return possible_results.contains(&equation.result)
-        })
-        .map(|equation| equation.result)
-        .sum();
+#[derive(Debug, PartialEq)]
+enum Undo {
+    Left(u64),
+    /// Whatever `left` is, it works out (like multiplying by zero)
+    Any,
+    Impossible,
+}
+
+struct Add;
+struct Multiply;
+struct Concat;
+
+impl Operator for Add {
+    fn symbol(&self) -> &'static str {
+        "+"
+    }
+
+    fn precedence(&self) -> u8 {
+        1
+    }
+
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_add(right)
+    }
 
-    println!("Part 1: {sum}");
+    fn undo(&self, result: u64, right: u64) -> Undo {
+        result.checked_sub(right).map_or(Undo::Impossible, Undo::Left)
+    }
 }
 
-fn part2() {
-    let equations = load_equations();
-    let sum: u64 = equations.iter()
-        .filter(|equation| {
-            let possible_results = get_possible_results(&equation.values, true);
-            return possible_results.contains(&equation.result)
-        })
-        .map(|equation| equation.result)
-        .sum();
+impl Operator for Multiply {
+    fn symbol(&self) -> &'static str {
+        "*"
+    }
+
+    fn precedence(&self) -> u8 {
+        2
+    }
+
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_mul(right)
+    }
 
-    println!("Part 2: {sum}");
+    fn undo(&self, result: u64, right: u64) -> Undo {
+        match (result, right) {
+            (0, 0) => Undo::Any,
+            (_, 0) => Undo::Impossible,
+            _ if result.is_multiple_of(right) => Undo::Left(result / right),
+            _ => Undo::Impossible,
+        }
+    }
 }
 
-fn get_possible_results(values: &[u64], allow_concat: bool) -> Vec<u64> {
-    let (last, rest) = values.split_last().unwrap();
-    if rest.is_empty() {
-        vec![*last]
-    } else {
-        get_possible_results(rest, allow_concat).into_iter()
-            .flat_map(|downstream_result| {
-                if allow_concat {
-                    let mut concatted = downstream_result.to_string();
-                    concatted.push_str(&last.to_string());
-                    let concatted: u64 = concatted.parse().unwrap();
-
-                    vec![last + downstream_result, last * downstream_result, concatted].into_iter()
+impl Operator for Concat {
+    fn symbol(&self) -> &'static str {
+        "||"
+    }
+
+    // gluing digits together binds tighter than anything else
+    fn precedence(&self) -> u8 {
+        3
+    }
+
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        left.checked_mul(digit_shift(right)?)?.checked_add(right)
+    }
+
+    // the result has to end in right's digits, and whatever's in front of them is left
+    fn undo(&self, result: u64, right: u64) -> Undo {
+        match digit_shift(right) {
+            Some(shift) if result % shift == right => Undo::Left(result / shift),
+            // right has as many digits as a u64 can hold, so left would have to be nothing
+            _ => Undo::Impossible,
+        }
+    }
+}
+
+/// 10 to the power of however many digits `value` has.
+fn digit_shift(value: u64) -> Option<u64> {
+    10_u64.checked_pow(value.checked_ilog10().unwrap_or(0) + 1)
+}
+
+const PART1_OPERATORS: [&dyn Operator; 2] = [&Add, &Multiply];
+const PART2_OPERATORS: [&dyn Operator; 3] = [&Add, &Multiply, &Concat];
+
+/// How an equation gets evaluated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Order {
+    /// Strictly left to right, like the elephants do it
+    LeftToRight,
+    /// Tighter operators first (so `*` before `+`), then left to right
+    Precedence,
+}
+
+impl Equation {
+    /// The operators (left to right, one between each pair of values) that make the values come
+    /// out to the result, if there are any. Left to right, this works backwards from the result,
+    /// so most choices fall apart within a value or two; with precedence, it tries everything.
+    fn solve<'a>(&self, operators: &[&'a dyn Operator], order: Order) -> Option<Vec<&'a dyn Operator>> {
+        match order {
+            Order::LeftToRight => {
+                let mut chosen = vec![];
+                if unwind(self.result, &self.values, operators, &mut chosen) {
+                    chosen.reverse();
+                    Some(chosen)
                 } else {
-                    vec![last + downstream_result, last * downstream_result].into_iter()
+                    None
                 }
-            })
-            .collect()
+            }
+            Order::Precedence => {
+                let mut found = None;
+                each_assignment(self.values.len() - 1, operators, &mut vec![], &mut |chosen| {
+                    if evaluate(&self.values, chosen, order) == Some(self.result) {
+                        found = Some(chosen.to_vec());
+                    }
+                    found.is_some()
+                });
+                found
+            }
+        }
+    }
+
+    /// How many different operator assignments make the values come out to the result.
+    fn count_solutions(&self, operators: &[&dyn Operator], order: Order) -> usize {
+        match order {
+            Order::LeftToRight => count_unwinds(self.result, &self.values, operators),
+            Order::Precedence => {
+                let mut count = 0;
+                each_assignment(self.values.len() - 1, operators, &mut vec![], &mut |chosen| {
+                    if evaluate(&self.values, chosen, order) == Some(self.result) {
+                        count += 1;
+                    }
+                    false
+                });
+                count
+            }
+        }
+    }
+
+    /// A working equation written out, like `190 = 10 * 19`.
+    fn expression(&self, operators: &[&dyn Operator], order: Order) -> Option<String> {
+        let chosen = self.solve(operators, order)?;
+        let mut expression = format!("{} = {}", self.result, self.values[0]);
+        for (operator, value) in chosen.iter().zip(&self.values[1..]) {
+            expression.push_str(&format!(" {} {value}", operator.symbol()));
+        }
+        Some(expression)
+    }
+}
+
+/// None if anything overflows along the way.
+fn evaluate(values: &[u64], operators: &[&dyn Operator], order: Order) -> Option<u64> {
+    let mut pairs = operators.iter().zip(&values[1..]);
+    match order {
+        Order::LeftToRight => pairs.try_fold(values[0], |acc, (operator, &value)| operator.apply(acc, value)),
+        Order::Precedence => {
+            let mut operands = vec![values[0]];
+            let mut pending: Vec<&dyn Operator> = vec![];
+            let reduce = |operands: &mut Vec<u64>, pending: &mut Vec<&dyn Operator>| {
+                let (right, left) = (operands.pop()?, operands.pop()?);
+                operands.push(pending.pop()?.apply(left, right)?);
+                Some(())
+            };
+
+            for (&operator, &value) in pairs {
+                while pending.last().is_some_and(|top| top.precedence() >= operator.precedence()) {
+                    reduce(&mut operands, &mut pending)?;
+                }
+                pending.push(operator);
+                operands.push(value);
+            }
+            while !pending.is_empty() {
+                reduce(&mut operands, &mut pending)?;
+            }
+            operands.pop()
+        }
     }
 }
 
-fn load_equations() -> Vec<Equation> {
-    fs::read_to_string("d07/input").unwrap()
+/// Peels operators off the right end of `values`; `chosen` ends up right to left.
+fn unwind<'a>(target: u64, values: &[u64], operators: &[&'a dyn Operator], chosen: &mut Vec<&'a dyn Operator>) -> bool {
+    let (&last, rest) = values.split_last().unwrap();
+    if rest.is_empty() {
+        return target == last;
+    }
+
+    for &operator in operators {
+        chosen.push(operator);
+        let found = match operator.undo(target, last) {
+            Undo::Left(left) => unwind(left, rest, operators, chosen),
+            Undo::Any => {
+                let mut prefix = None;
+                each_assignment(rest.len() - 1, operators, &mut vec![], &mut |ops| {
+                    if evaluate(rest, ops, Order::LeftToRight).is_some() {
+                        prefix = Some(ops.to_vec());
+                    }
+                    prefix.is_some()
+                });
+                prefix.map(|prefix| chosen.extend(prefix.into_iter().rev())).is_some()
+            }
+            Undo::Impossible => false,
+        };
+        if found {
+            return true;
+        }
+        chosen.pop();
+    }
+
+    false
+}
+
+/// Like `unwind`, but keeps going to count every way there is.
+fn count_unwinds(target: u64, values: &[u64], operators: &[&dyn Operator]) -> usize {
+    let (&last, rest) = values.split_last().unwrap();
+    if rest.is_empty() {
+        return (target == last) as usize;
+    }
+
+    operators.iter()
+        .map(|operator| match operator.undo(target, last) {
+            Undo::Left(left) => count_unwinds(left, rest, operators),
+            Undo::Any => {
+                let mut count = 0;
+                each_assignment(rest.len() - 1, operators, &mut vec![], &mut |ops| {
+                    if evaluate(rest, ops, Order::LeftToRight).is_some() {
+                        count += 1;
+                    }
+                    false
+                });
+                count
+            }
+            Undo::Impossible => 0,
+        })
+        .sum()
+}
+
+/// Hands every way of filling `slots` with operators to `visit`, until it returns true. True if
+/// it did.
+fn each_assignment<'a>(
+    slots: usize,
+    operators: &[&'a dyn Operator],
+    chosen: &mut Vec<&'a dyn Operator>,
+    visit: &mut impl FnMut(&[&'a dyn Operator]) -> bool,
+) -> bool {
+    if chosen.len() == slots {
+        return visit(chosen);
+    }
+
+    for &operator in operators {
+        chosen.push(operator);
+        let stop = each_assignment(slots, operators, chosen, visit);
+        chosen.pop();
+        if stop {
+            return true;
+        }
+    }
+
+    false
+}
+
+struct Config {
+    order: Order,
+    // print how each equation works out to stderr
+    show: bool,
+}
+
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { order: Order::LeftToRight, show: false };
+        for arg in args {
+            match arg.as_str() {
+                "--precedence" => config.order = Order::Precedence,
+                "--show" => config.show = true,
+                _ => return Err(format!("Unrecognized argument {arg:?}; d07 takes --precedence and --show")),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn main() {
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d07", &message));
+
+    runner::run("d07", load_equations, |equations| part1(equations, &config), |equations| part2(equations, &config));
+}
+
+fn part1(equations: &[Equation], config: &Config) -> u64 {
+    calibration(equations, &PART1_OPERATORS, config)
+}
+
+fn part2(equations: &[Equation], config: &Config) -> u64 {
+    calibration(equations, &PART2_OPERATORS, config)
+}
+
+fn calibration(equations: &[Equation], operators: &[&dyn Operator], config: &Config) -> u64 {
+    equations.iter()
+        .filter(|equation| {
+            if config.show {
+                if let Some(expression) = equation.expression(operators, config.order) {
+                    let ways = equation.count_solutions(operators, config.order);
+                    eprintln!("{expression}  ({ways} way{})", if ways == 1 { "" } else { "s" });
+                }
+            }
+            equation.solve(operators, config.order).is_some()
+        })
+        .map(|equation| equation.result)
+        .sum()
+}
+
+fn load_equations(input: &str) -> ParseResult<Vec<Equation>> {
+    let source = Source::new(input);
+    input
         .lines()
         .map(|line| {
-            let mut parts = line.split(": ");
-            let result = parts.next().unwrap().parse().unwrap();
-            let values = parts.next().unwrap()
-                .split(" ")
-                .map(|value| value.parse().unwrap())
-                .collect();
-
-            return Equation {result, values};
+            let (result, values) = source.key_value(line)?;
+            let values: Vec<u64> = source.list(values, "a number")?;
+            if values.is_empty() {
+                return Err(source.missing(line, "a number"));
+            }
+            Ok(Equation { result: source.parse(result, "a test value")?, values })
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "190: 10 19\n3267: 81 40 27\n83: 17 5\n156: 15 6\n7290: 6 8 6 15\n\
+                           161011: 16 10 13\n192: 17 8 14\n21037: 9 7 18 13\n292: 11 6 16 20\n";
+
+    fn symbols(operators: &[&dyn Operator]) -> Vec<&'static str> {
+        operators.iter().map(|operator| operator.symbol()).collect()
+    }
+
+    const LEFT_TO_RIGHT: Config = Config { order: Order::LeftToRight, show: false };
+
+    #[test]
+    fn example() {
+        let equations = load_equations(EXAMPLE).unwrap();
+        assert_eq!(part1(&equations, &LEFT_TO_RIGHT), 3749);
+        assert_eq!(part2(&equations, &LEFT_TO_RIGHT), 11387);
+    }
+
+    #[test]
+    fn assignments() {
+        let equations = load_equations(EXAMPLE).unwrap();
+        let solved = |idx: usize, operators: &[&dyn Operator]| equations[idx].solve(operators, Order::LeftToRight).map(|ops| symbols(&ops));
+
+        assert_eq!(solved(0, &PART1_OPERATORS), Some(vec!["*"]));
+        assert_eq!(solved(8, &PART1_OPERATORS), Some(vec!["+", "*", "+"]));
+        assert_eq!(solved(4, &PART1_OPERATORS), None);
+        assert_eq!(solved(4, &PART2_OPERATORS), Some(vec!["*", "||", "*"]));
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(Concat.apply(12, 345), Some(12345));
+        assert_eq!(Concat.apply(u64::MAX / 10, 9), None);
+        assert_eq!(Concat.undo(12345, 45), Undo::Left(123));
+        assert_eq!(Concat.undo(12345, 46), Undo::Impossible);
+        assert_eq!(Multiply.undo(0, 0), Undo::Any);
+
+        let huge = Equation { result: u64::MAX, values: vec![u64::MAX, 1, 0] };
+        assert_eq!(huge.solve(&PART2_OPERATORS, Order::LeftToRight).map(|ops| symbols(&ops)), Some(vec!["*", "+"]));
+        // times zero works for anything in front of it, as long as that doesn't overflow
+        let zero = Equation { result: 0, values: vec![u64::MAX, 1, 0] };
+        assert_eq!(zero.solve(&PART1_OPERATORS, Order::LeftToRight).map(|ops| symbols(&ops)), Some(vec!["*", "*"]));
+        let overflowing = Equation { result: 0, values: vec![u64::MAX, u64::MAX, 0] };
+        assert!(overflowing.solve(&PART1_OPERATORS, Order::LeftToRight).is_none());
+    }
+
+    #[test]
+    fn expressions_and_counts() {
+        let equations = load_equations(EXAMPLE).unwrap();
+        let ltr = Order::LeftToRight;
+        assert_eq!(equations[0].expression(&PART1_OPERATORS, ltr).as_deref(), Some("190 = 10 * 19"));
+        assert_eq!(equations[3].expression(&PART2_OPERATORS, ltr).as_deref(), Some("156 = 15 || 6"));
+        assert_eq!(equations[2].expression(&PART2_OPERATORS, ltr), None);
+
+        // 81 + 40 * 27 and 81 * 40 + 27
+        assert_eq!(equations[1].count_solutions(&PART1_OPERATORS, ltr), 2);
+        assert_eq!(equations[2].count_solutions(&PART2_OPERATORS, ltr), 0);
+        // anything times zero
+        let zero = Equation { result: 0, values: vec![2, 3, 0] };
+        assert_eq!(zero.count_solutions(&PART1_OPERATORS, ltr), 2);
+    }
+
+    #[test]
+    fn precedence() {
+        let prec = Order::Precedence;
+        assert_eq!(evaluate(&[2, 3, 4], &[&Add, &Multiply], prec), Some(14));
+        assert_eq!(evaluate(&[2, 3, 4], &[&Add, &Multiply], Order::LeftToRight), Some(20));
+        assert_eq!(evaluate(&[2, 3, 4, 5], &[&Multiply, &Concat, &Add], prec), Some(73));
+
+        let equation = Equation { result: 14, values: vec![2, 3, 4] };
+        assert_eq!(equation.expression(&PART1_OPERATORS, prec).as_deref(), Some("14 = 2 + 3 * 4"));
+        assert_eq!(equation.count_solutions(&PART1_OPERATORS, Order::LeftToRight), 0);
+
+        // 81 + 40 * 27 doesn't work any more
+        let equations = load_equations(EXAMPLE).unwrap();
+        assert_eq!(equations[1].count_solutions(&PART1_OPERATORS, prec), 1);
+        assert!(Config::from_args(vec![String::from("--backwards")]).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use lib2d::Point2d;
+use libparse::ParseResult;
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 struct Map {
     max_x: i64,
@@ -15,15 +15,13 @@ impl Map {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d08", load_map, part1, part2);
 }
 
-fn part1() {
-    let map = load_map();
+fn part1(map: &Map) -> usize {
     let mut antinodes: HashSet<Point2d<i64>> = HashSet::new();
     map.antennas.values().for_each(|points| {
-        let pairs = pairs(&points);
+        let pairs = pairs(points);
         for (a, b) in pairs {
             let delta = a - b;
             let lower = a + delta;
@@ -36,14 +34,13 @@ fn part1() {
         }
     });
 
-    println!("Part 1: {:?}", antinodes.len());
+    antinodes.len()
 }
 
-fn part2() {
-    let map = load_map();
+fn part2(map: &Map) -> usize {
     let mut antinodes: HashSet<Point2d<i64>> = HashSet::new();
     map.antennas.values().for_each(|points| {
-        let pairs = pairs(&points);
+        let pairs = pairs(points);
         for (a, b) in pairs {
             let delta = a - b;
             
@@ -69,8 +66,7 @@ fn part2() {
         }
     });
 
-    println!("Part 2: {:?}", antinodes.len());
-
+    antinodes.len()
 }
 
 fn pairs<T: Clone>(list: &[T]) -> Vec<(T, T)> {
@@ -85,22 +81,23 @@ fn pairs<T: Clone>(list: &[T]) -> Vec<(T, T)> {
     let mut downstream = pairs(rest);
     list.append(&mut downstream);
 
-    return list
+    list
 }
 
-fn load_map() -> Map {
+fn load_map(input: &str) -> ParseResult<Map> {
+    // anything other than '.' is an antenna, so there's nothing to get wrong
     let mut max_x = 0;
     let mut max_y = 0;
-    let mut antennas = HashMap::new();
+    let mut antennas: HashMap<char, Vec<Point2d<i64>>> = HashMap::new();
 
-    fs::read_to_string("d08/input").unwrap()
+    input
         .lines().enumerate()
         .for_each(|(y, line)| {
             max_y = y as i64;
             line.chars().enumerate()
                 .for_each(|(x, char)| {
                     if char != '.' {
-                        let mut list = antennas.remove(&char).unwrap_or_else(|| vec![]);
+                        let mut list = antennas.remove(&char).unwrap_or_default();
                         list.push(Point2d::new(x as i64, y as i64));
                         antennas.insert(char, list);
                     }
@@ -108,7 +105,7 @@ fn load_map() -> Map {
                 })
         });
 
-    Map {max_x, max_y, antennas}
+    Ok(Map {max_x, max_y, antennas})
 }
 
 #[cfg(test)]
@@ -1,11 +1,11 @@
-use crate::BlockType::FILE;
-use std::fs;
-use BlockType::FREE;
+use crate::BlockType::File;
+use BlockType::Free;
+use libparse::{ParseResult, Source};
 
 #[derive(PartialEq, Clone, Copy)]
 enum BlockType {
-    FILE,
-    FREE,
+    File,
+    Free,
 }
 
 struct FreeSpace {
@@ -95,12 +95,11 @@ impl Drive {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d09", read_digits, |digits| part1(digits), |digits| part2(digits));
 }
 
-fn part1() {
-    let mut array = get_array();
+fn part1(digits: &[usize]) -> i64 {
+    let mut array = get_array(digits);
     let mut front_pointer: usize = 0;
     while front_pointer < array.len() {
         if array[front_pointer] > -1 {
@@ -119,40 +118,35 @@ fn part1() {
         }
     }
 
-    let score: i64 = array.iter().enumerate().map(|(i, v)| i as i64 * v).sum();
-    println!("Part 1: {score}");
+    array.iter().enumerate().map(|(i, v)| i as i64 * v).sum()
 }
 
-fn part2() {
-    let mut drive = get_drive();
+fn part2(digits: &[usize]) -> usize {
+    let mut drive = get_drive(digits);
     drive.defrag();
-    let checksum = drive.checksum();
-
-    println!("Part 2: {checksum}");
+    drive.checksum()
 }
 
-fn get_array() -> Vec<i64> {
-    let mut cur_type = FILE;
+fn get_array(digits: &[usize]) -> Vec<i64> {
+    let mut cur_type = File;
     let mut cur_id = 0;
     let mut output = vec![];
 
-    fs::read_to_string("d09/input")
-        .unwrap()
-        .chars()
-        .for_each(|d| {
-            let size = d.to_string().parse().unwrap();
+    digits
+        .iter()
+        .for_each(|&size| {
             let block_id = match cur_type {
-                FREE => -1,
-                FILE => cur_id,
+                Free => -1,
+                File => cur_id,
             };
             for _ in 0..size {
                 output.push(block_id);
             }
             match cur_type {
-                FREE => cur_type = FILE,
-                FILE => {
+                Free => cur_type = File,
+                File => {
                     cur_id += 1;
-                    cur_type = FREE
+                    cur_type = Free
                 }
             }
         });
@@ -160,26 +154,33 @@ fn get_array() -> Vec<i64> {
     output
 }
 
-fn get_drive() -> Drive {
+fn get_drive(digits: &[usize]) -> Drive {
     let mut drive = Drive::new();
-    let mut cur_type = FILE;
+    let mut cur_type = File;
 
-    fs::read_to_string("d09/input")
-        .unwrap()
-        .chars()
-        .for_each(|d| {
-            let size: usize = d.to_string().parse().unwrap();
+    digits
+        .iter()
+        .for_each(|&size| {
             match cur_type {
-                FILE => {
+                File => {
                     drive.insert_file(size);
-                    cur_type = FREE;
+                    cur_type = Free;
                 }
-                FREE => {
+                Free => {
                     drive.insert_space(size);
-                    cur_type = FILE
+                    cur_type = File
                 }
             };
         });
 
     drive
 }
+
+fn read_digits(input: &str) -> ParseResult<Vec<usize>> {
+    let source = Source::new(input);
+    input
+        .trim_end()
+        .char_indices()
+        .map(|(idx, d)| source.parse(&input[idx..idx + d.len_utf8()], "a digit"))
+        .collect()
+}
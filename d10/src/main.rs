@@ -1,92 +1,270 @@
-use lib2d::Direction::{Down, Left, Right};
-use lib2d::{dir_delta, Direction, Point2d};
-use std::collections::{HashMap, HashSet};
-use std::fs;
-use Direction::Up;
+use lib2d::Direction::{Down, Left, Right, Up};
+use lib2d::{dir_delta, Grid, Point2d};
+use libparse::{ParseResult, Source};
+use std::collections::HashMap;
 
 type Coord = Point2d<i32>;
-type Height = usize;
+type Height = i32;
 
-fn main() {
-    part1();
-    part2();
+type Map = Grid<Height>;
+
+/// Which heights a trail goes through: `start`, `start + step`, and so on, until `end`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TrailRule {
+    start: Height,
+    end: Height,
+    step: Height,
+}
+
+impl TrailRule {
+    fn new(start: Height, end: Height, step: Height) -> Result<TrailRule, String> {
+        let rise = end - start;
+        if step == 0 || rise % step != 0 || rise.signum() * step.signum() < 0 {
+            return Err(format!("Steps of {step} never get from {start} to {end}"));
+        }
+        Ok(TrailRule { start, end, step })
+    }
+
+    /// Every height along the way, from the end back to the start
+    fn heights_backwards(&self) -> impl Iterator<Item = Height> + '_ {
+        let levels = (self.end - self.start) / self.step;
+        (0..=levels).rev().map(|level| self.start + level * self.step)
+    }
 }
 
-fn part1() {
-    let map = read_map();
-    let trailheads: Vec<&Coord> = map.iter()
-        .filter(|(_p, &h)| h == 0)
-        .map(|(p, _h)| p)
-        .collect();
-
-    let score: usize = trailheads.iter()
-        .map(|point| pathfind(&map, point, &vec![]))
-        .map(|list_of_paths| {
-            let distinct_ends: HashSet<Coord> = list_of_paths.iter()
-                .flat_map(|path| path.last().map(|c| c.clone()))
-                .collect();
-
-            distinct_ends
-        })
-        .map(|points| points.len())
-        .sum();
-
-    println!("Part 1: {score}");
+struct Config {
+    rule: TrailRule,
+    // print this many of the best trails to stderr
+    top: Option<usize>,
 }
 
-fn part2() {
-    let map = read_map();
-    let trailheads: Vec<&Coord> = map.iter()
-        .filter(|(_p, &h)| h == 0)
-        .map(|(p, _h)| p)
-        .collect();
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let (mut start, mut end, mut step) = (0, 9, 1);
+        let mut top = None;
 
-    let score: usize = trailheads.iter()
-        .map(|point| pathfind(&map, point, &vec![]))
-        .map(|paths| paths.len())
-        .sum();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--heights" => {
+                    let spec = args.next().ok_or("--heights needs a range, like 0..9")?;
+                    let bad_heights = || format!("Expected heights like 0..9, not {spec:?}");
+                    let (from, to) = spec.split_once("..").ok_or_else(bad_heights)?;
+                    start = from.parse().map_err(|_| bad_heights())?;
+                    end = to.parse().map_err(|_| bad_heights())?;
+                }
+                "--step" => {
+                    let spec = args.next().ok_or("--step needs a number")?;
+                    step = spec.parse().map_err(|_| format!("Expected a step, not {spec:?}"))?;
+                }
+                "--top" => {
+                    let spec = args.next().ok_or("--top needs a number of trails")?;
+                    top = Some(spec.parse().map_err(|_| format!("Expected a number of trails, not {spec:?}"))?);
+                }
+                _ => return Err(format!("Unrecognized argument {arg:?}; d10 takes --heights, --step, and --top")),
+            }
+        }
+
+        Ok(Config { rule: TrailRule::new(start, end, step)?, top })
+    }
+}
 
-    println!("Part 2: {score}");
+/// Scores and ratings for every trailhead, worked out a height at a time from the ends back.
+struct Network<'a> {
+    map: &'a Map,
+    rule: TrailRule,
+    // how many distinct trails lead from each tile to an end
+    ratings: Grid<u64>,
+    // how many distinct ends each trailhead can reach
+    scores: HashMap<Coord, usize>,
 }
 
-fn pathfind(map: &HashMap<Coord, Height>,
-            from_point: &Coord,
-            path: &Vec<Coord>) -> Vec<Vec<Coord>> {
-    let self_height = map.get(from_point).unwrap();
+impl<'a> Network<'a> {
+    /// Each tile's rating is just the sum of the ratings of the tiles one step on from it, so
+    /// going through the heights backwards sees every tile (and edge) once: ratings are linear.
+    /// Scores aren't. They need to know *which* ends are reachable, so each tile carries a bitset
+    /// of them, and OR-ing those costs O(tiles * ends / 64) all told (counting the other way,
+    /// with bitsets of trailheads, would be O(tiles * trailheads / 64)). Only the last height's
+    /// bitsets are kept around.
+    fn new(map: &'a Map, rule: TrailRule) -> Network<'a> {
+        let mut by_height: HashMap<Height, Vec<Coord>> = HashMap::new();
+        for (point, height) in map.iter() {
+            by_height.entry(*height).or_default().push(point);
+        }
+        let no_tiles = vec![];
+        let tiles_at = |height: Height| by_height.get(&height).unwrap_or(&no_tiles);
 
-    let mut own_path = path.clone();
-    own_path.push(from_point.clone());
+        let words = tiles_at(rule.end).len().div_ceil(64);
+        let mut ratings = Grid::new(map.width(), map.height(), 0);
+        let mut reachable: HashMap<Coord, Vec<u64>> = HashMap::new();
 
-    if *self_height == 9 {
-        return vec![own_path];
+        for height in rule.heights_backwards() {
+            let mut next_reachable = HashMap::new();
+            // at the end height, the tiles are the ends, in the same order
+            for (end_idx, &point) in tiles_at(height).iter().enumerate() {
+                let mut ends_here = vec![0_u64; words];
+                if height == rule.end {
+                    ends_here[end_idx / 64] |= 1 << (end_idx % 64);
+                    ratings[point] = 1;
+                } else {
+                    for next in uphill(map, point, rule.step) {
+                        ratings[point] += ratings[next];
+                        for (word, other) in ends_here.iter_mut().zip(&reachable[&next]) {
+                            *word |= other;
+                        }
+                    }
+                }
+                next_reachable.insert(point, ends_here);
+            }
+            reachable = next_reachable;
+        }
+
+        let scores = reachable.into_iter()
+            .map(|(point, ends)| (point, ends.iter().map(|word| word.count_ones() as usize).sum()))
+            .collect();
+
+        Network { map, rule, ratings, scores }
+    }
+
+    fn score(&self) -> usize {
+        self.scores.values().sum()
     }
 
-    let mut child_paths = vec![];
-    for dir in [Up, Down, Left, Right] {
-        let neighbor_point = *from_point + dir_delta(dir);
-        if let Some(&neighbor_height) = map.get(&neighbor_point) {
-            if neighbor_height == self_height + 1 {
-                let downstream = pathfind(map, &neighbor_point, &own_path);
-                child_paths.extend(downstream);
+    fn rating(&self) -> u64 {
+        self.scores.keys().map(|point| self.ratings[*point]).sum()
+    }
+
+    /// Up to `k` whole trails, from the best-rated trailheads first. Only steps onto tiles that
+    /// lead somewhere, so each trail costs just its own length.
+    fn top_trails(&self, k: usize) -> Vec<Vec<Coord>> {
+        let mut trailheads: Vec<Coord> = self.scores.keys().copied().collect();
+        trailheads.sort_by_key(|point| (std::cmp::Reverse(self.ratings[*point]), *point));
+
+        let mut trails = vec![];
+        for trailhead in trailheads {
+            self.collect_trails(&mut vec![trailhead], k, &mut trails);
+            if trails.len() >= k {
+                break;
             }
         }
+        trails
     }
 
-    child_paths
+    fn collect_trails(&self, trail: &mut Vec<Coord>, k: usize, trails: &mut Vec<Vec<Coord>>) {
+        let here = *trail.last().unwrap();
+        if self.map[here] == self.rule.end {
+            trails.push(trail.clone());
+            return;
+        }
+
+        for next in uphill(self.map, here, self.rule.step) {
+            if trails.len() >= k {
+                return;
+            }
+            if self.ratings[next] > 0 {
+                trail.push(next);
+                self.collect_trails(trail, k, trails);
+                trail.pop();
+            }
+        }
+    }
+}
+
+/// The tiles next to `point` that are exactly one step on from it.
+fn uphill(map: &Map, point: Coord, step: Height) -> impl Iterator<Item = Coord> + '_ {
+    let height = map[point];
+    [Up, Down, Left, Right].into_iter()
+        .map(move |dir| point + dir_delta(dir))
+        .filter(move |next| map.get(*next) == Some(&(height + step)))
 }
 
-fn read_map() -> HashMap<Coord, Height> {
-    let mut map = HashMap::new();
-
-    fs::read_to_string("d10/input").unwrap()
-        .lines().enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate()
-                .for_each(|(x, d)| {
-                    let height = d.to_string().parse().unwrap();
-                    map.insert(Point2d::new(x as i32, y as i32), height);
-                });
-        });
-
-    map
-}
\ No newline at end of file
+fn main() {
+    let config = Config::from_args(runner::day_args())
+        .unwrap_or_else(|message| runner::bad_day_args("d10", &message));
+
+    runner::run("d10", read_map, |map| part1(map, &config), |map| part2(map, &config));
+}
+
+fn part1(map: &Map, config: &Config) -> usize {
+    let network = Network::new(map, config.rule);
+    if let Some(k) = config.top {
+        for trail in network.top_trails(k) {
+            let points: Vec<String> = trail.iter().map(|point| format!("{},{}", point.x, point.y)).collect();
+            eprintln!("{}", points.join(" -> "));
+        }
+    }
+
+    network.score()
+}
+
+fn part2(map: &Map, config: &Config) -> u64 {
+    Network::new(map, config.rule).rating()
+}
+
+fn read_map(input: &str) -> ParseResult<Map> {
+    let source = Source::new(input);
+    let mut rows: Vec<Vec<Height>> = vec![];
+
+    for line in input.lines() {
+        let row = line.char_indices()
+            .map(|(idx, d)| source.parse(&line[idx..idx + d.len_utf8()], "a height (0-9)"))
+            .collect::<ParseResult<Vec<_>>>()?;
+        if rows.first().is_some_and(|first| first.len() != row.len()) {
+            return Err(source.error(line, format!("a row {} heights wide", rows[0].len())));
+        }
+        rows.push(row);
+    }
+
+    Ok(Grid::from_rows(rows).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const EXAMPLE: &str = "89010123\n78121874\n87430965\n96549874\n45678903\n32019012\n01329801\n10456732\n";
+
+    fn config(args: &[&str]) -> Config {
+        Config::from_args(args.iter().map(|arg| arg.to_string()).collect()).unwrap()
+    }
+
+    #[test]
+    fn example() {
+        let map = read_map(EXAMPLE).unwrap();
+        assert_eq!(part1(&map, &config(&[])), 36);
+        assert_eq!(part2(&map, &config(&[])), 81);
+    }
+
+    #[test]
+    fn other_rules() {
+        let map = read_map(EXAMPLE).unwrap();
+        // the same trails (and pairs of ends), walked the other way
+        assert_eq!(part1(&map, &config(&["--heights", "9..0", "--step", "-1"])), 36);
+        assert_eq!(part2(&map, &config(&["--heights", "9..0", "--step", "-1"])), 81);
+        assert_eq!(part1(&map, &config(&["--heights", "1..8"])), 19);
+
+        let map = read_map("0246\n1357\n").unwrap();
+        assert_eq!(part2(&map, &config(&["--heights", "0..6", "--step", "2"])), 1);
+        assert!(TrailRule::new(0, 9, 2).is_err());
+        assert!(TrailRule::new(0, 9, -1).is_err());
+    }
+
+    #[test]
+    fn top_trails() {
+        let map = read_map(EXAMPLE).unwrap();
+        let network = Network::new(&map, TrailRule::new(0, 9, 1).unwrap());
+        let trails = network.top_trails(25);
+        assert_eq!(trails.len(), 25);
+
+        // the best trailhead has a rating of 24, so its trails come first
+        assert!(trails[..24].iter().all(|trail| trail[0] == trails[0][0]));
+        assert_eq!(network.ratings[trails[0][0]], 24);
+        for trail in trails.iter() {
+            let heights: Vec<Height> = trail.iter().map(|point| map[*point]).collect();
+            assert_eq!(heights, (0..=9).collect::<Vec<_>>());
+        }
+        let distinct: HashSet<&Vec<Coord>> = trails.iter().collect();
+        assert_eq!(distinct.len(), trails.len());
+    }
+}
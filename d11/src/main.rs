@@ -1,95 +1,217 @@
-use std::collections::HashMap;
-use std::fs;
+use libparse::{ParseResult, Source};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 
-fn main() {
-    part1();
-    part2();
-}
-
-fn part1() {
-    let mut stones = load_stones();
-    for _ in 0..25 {
-        let mut new_stones = vec![];
-        for stone in stones.iter() {
-            let stone_str = stone.to_string();
-            if stone == &0 {
-                new_stones.push(1);
-            } else if stone_str.len() % 2 == 0 {
-                let half = stone_str.len()/2;
-                new_stones.push(stone_str[..half].parse().unwrap());
-                new_stones.push(stone_str[half..].parse().unwrap());
-            } else {
-                new_stones.push(stone * 2024)
+/// How many of each stone there are. Order doesn't change what any stone turns into, so there's
+/// no need to keep track of it.
+type Stones = HashMap<u64, u64>;
+
+/// One of the things a stone can do when you blink: the first rule whose `applies` is true for a
+/// stone says what it turns into. A rule with no `applies` is for every stone. `transform` gives
+/// back None when a stone would turn into one too big to hold.
+struct Rule {
+    name: &'static str,
+    applies: Option<Box<dyn Fn(u64) -> bool>>,
+    transform: Box<dyn Fn(u64) -> Option<Vec<u64>>>,
+}
+
+impl Rule {
+    fn new(
+        name: &'static str,
+        applies: impl Fn(u64) -> bool + 'static,
+        transform: impl Fn(u64) -> Option<Vec<u64>> + 'static,
+    ) -> Rule {
+        Rule { name, applies: Some(Box::new(applies)), transform: Box::new(transform) }
+    }
+
+    fn always(name: &'static str, transform: impl Fn(u64) -> Option<Vec<u64>> + 'static) -> Rule {
+        Rule { name, applies: None, transform: Box::new(transform) }
+    }
+}
+
+/// 0 becomes 1, stones with an even number of digits split down the middle, and everything else
+/// gets multiplied by 2024.
+fn standard_rules() -> Vec<Rule> {
+    vec![
+        Rule::new("zero", |stone| stone == 0, |_| Some(vec![1])),
+        Rule::new("split", |stone| digits(stone).is_multiple_of(2), |stone| {
+            let (left, right) = split_digits(stone);
+            Some(vec![left, right])
+        }),
+        Rule::always("multiply", |stone| Some(vec![stone.checked_mul(2024)?])),
+    ]
+}
+
+/// A stone that turned into one bigger than a u64.
+#[derive(Debug, PartialEq)]
+struct TooBig {
+    stone: u64,
+    rule: &'static str,
+}
+
+impl Display for TooBig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "stone {} got too big to keep track of when the {} rule went at it", self.stone, self.rule)
+    }
+}
+
+fn digits(value: u64) -> u32 {
+    value.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// The first half of the digits, and the second; `1000` is `10` and `0`.
+fn split_digits(value: u64) -> (u64, u64) {
+    let half = 10_u64.pow(digits(value) / 2);
+    (value / half, value % half)
+}
+
+struct Blinker {
+    rules: Vec<Rule>,
+    // for the stones none of `rules` apply to
+    otherwise: Rule,
+}
+
+impl Blinker {
+    /// Every stone has to turn into something, so the last rule has to be one for every stone.
+    fn new(mut rules: Vec<Rule>) -> Result<Blinker, String> {
+        match rules.pop() {
+            Some(otherwise) if otherwise.applies.is_none() => Ok(Blinker { rules, otherwise }),
+            Some(last) => Err(format!("the last rule, {}, has to apply to every stone", last.name)),
+            None => Err(String::from("there has to be at least one rule")),
+        }
+    }
+
+    fn blink(&self, stones: &Stones) -> Result<Stones, TooBig> {
+        let mut next = Stones::new();
+        for (&stone, &count) in stones {
+            let rule = self.rule_for(stone);
+            for child in (rule.transform)(stone).ok_or(TooBig { stone, rule: rule.name })? {
+                *next.entry(child).or_default() += count;
             }
         }
-        stones = new_stones;
+        Ok(next)
+    }
+
+    /// The stones after blinking `blinks` times.
+    fn generation(&self, stones: &[u64], blinks: usize) -> Result<Stones, TooBig> {
+        let mut current = Stones::new();
+        for &stone in stones {
+            *current.entry(stone).or_default() += 1;
+        }
+        for _ in 0..blinks {
+            current = self.blink(&current)?;
+        }
+        Ok(current)
+    }
+
+    fn count_after(&self, stones: &[u64], blinks: usize) -> Result<u64, TooBig> {
+        Ok(self.generation(stones, blinks)?.values().sum())
+    }
+
+    fn rule_for(&self, stone: u64) -> &Rule {
+        self.rules.iter()
+            .find(|rule| rule.applies.as_ref().is_some_and(|applies| applies(stone)))
+            .unwrap_or(&self.otherwise)
+    }
+}
+
+/// How many of each stone value there are, smallest value first.
+fn histogram(stones: &Stones) -> BTreeMap<u64, u64> {
+    stones.iter().map(|(&stone, &count)| (stone, count)).collect()
+}
+
+fn main() {
+    let histogram_at = match runner::day_args().as_slice() {
+        [] => None,
+        [flag, blinks] if flag == "--histogram" => Some(blinks.parse().unwrap_or_else(|_| {
+            runner::bad_day_args("d11", &format!("Expected a number of blinks, not {blinks:?}"))
+        })),
+        _ => runner::bad_day_args("d11", "d11 takes --histogram BLINKS"),
+    };
+
+    let blinker = Blinker::new(standard_rules()).expect("the standard rules end with multiplying every stone");
+    runner::run("d11", load_stones, |stones| part1(stones, &blinker, histogram_at), |stones| part2(stones, &blinker));
+}
+
+fn part1(stones: &[u64], blinker: &Blinker, histogram_at: Option<usize>) -> Option<u64> {
+    if let Some(blinks) = histogram_at {
+        let stones = reported(blinker.generation(stones, blinks))?;
+        eprintln!("After {blinks} blinks, {} different stones:", stones.len());
+        for (stone, count) in histogram(&stones) {
+            eprintln!("{stone:>16} x {count} (next: {})", blinker.rule_for(stone).name);
+        }
     }
 
-    println!("Part 1: {:?}", stones.len());
+    reported(blinker.count_after(stones, 25))
+}
+
+fn part2(stones: &[u64], blinker: &Blinker) -> Option<u64> {
+    reported(blinker.count_after(stones, 75))
+}
+
+/// What happened to the stones, or nothing (after saying why) if one got too big.
+fn reported<T>(result: Result<T, TooBig>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            eprintln!("d11: {err}");
+            None
+        }
+    }
 }
 
-type NumberTicks = (u64, usize);
-fn part2() {
-    let stones = load_stones();
-    let mut memo: HashMap<NumberTicks, usize> = HashMap::new();
-    
-    let size: usize = stones.iter()
-        .map(|stone| calculate_size(*stone, 75, &mut memo))
-        .sum();
-    
-    println!("Part 2: {size}")
+fn load_stones(input: &str) -> ParseResult<Vec<u64>> {
+    let source = Source::new(input);
+    source.list(input, "a stone number")
 }
 
-fn calculate_size(stone_value: u64, number_ticks: usize, memo: &mut HashMap<NumberTicks, usize>) -> usize {
-    // no ticks left means this stone isn't splitting
-    if number_ticks == 0 {
-        return 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let blinker = Blinker::new(standard_rules()).unwrap();
+        assert_eq!(blinker.count_after(&[125, 17], 6), Ok(22));
+        assert_eq!(part1(&[125, 17], &blinker, None), Some(55312));
+
+        let after_one = histogram(&blinker.generation(&[125, 17], 1).unwrap());
+        assert_eq!(after_one, BTreeMap::from([(1, 1), (7, 1), (253000, 1)]));
+        // 2024 splits into 20 and 24, then 2, 0, 2, and 4
+        let from_2024 = histogram(&blinker.generation(&[2024], 2).unwrap());
+        assert_eq!(from_2024, BTreeMap::from([(0, 1), (2, 2), (4, 1)]));
+        let names: Vec<&str> = [0, 1000, 7].iter().map(|stone| blinker.rule_for(*stone).name).collect();
+        assert_eq!(names, ["zero", "split", "multiply"]);
     }
-    
-    // if we have already calculated how large this stone will be after N ticks, use that
-    if let Some(known) = memo.get(&(stone_value, number_ticks)) {
-        return *known;
+
+    #[test]
+    fn digit_math() {
+        assert_eq!((digits(0), digits(9), digits(10), digits(u64::MAX)), (1, 1, 2, 20));
+        assert_eq!(split_digits(1000), (10, 0));
+        assert_eq!(split_digits(253000), (253, 0));
+        assert_eq!(split_digits(17), (1, 7));
     }
-    
-    // if this stone is a '0' 
-    if stone_value == 0 {
-        // calculate how big a '1' would be with 1 less tick
-        let size = calculate_size(1, number_ticks - 1, memo);
-        
-        // memorize and return this value
-        memo.insert((stone_value, number_ticks), size);
-        return size;
+
+    #[test]
+    fn huge_stones() {
+        let blinker = Blinker::new(standard_rules()).unwrap();
+        let stone = 10_u64.pow(18) + 1;
+        assert_eq!(blinker.count_after(&[stone], 1), Err(TooBig { stone, rule: "multiply" }));
+        assert_eq!(part2(&[stone], &blinker), None);
     }
-    
-    // if this stone has an even number of digits
-    let stone_str = stone_value.to_string();
-    if stone_str.len() % 2 == 0 {
-        // split it in half
-        let half = stone_str.len() / 2;
-        let child1 = stone_str[..half].parse().unwrap();
-        let child2 = stone_str[half..].parse().unwrap();
-        
-        // figure out how large each child would be with 1 fewer tick
-        let size1 = calculate_size(child1, number_ticks - 1, memo);
-        let size2 = calculate_size(child2, number_ticks - 1, memo);
-        
-        // sum the 2 children sizes, and memorize it
-        let sum = size1 + size2;
-        memo.insert((stone_value, number_ticks), sum);
-        
-        return sum;
+
+    #[test]
+    fn other_rules() {
+        // halve the even stones, triple the odd ones
+        let blinker = Blinker::new(vec![
+            Rule::new("halve", |stone| stone % 2 == 0, |stone| Some(vec![stone / 2, stone / 2])),
+            Rule::always("triple", |stone| Some(vec![stone.checked_mul(3)?])),
+        ])
+        .unwrap();
+        assert_eq!(histogram(&blinker.generation(&[4, 3], 2).unwrap()), BTreeMap::from([(1, 4), (27, 1)]));
+
+        // something has to happen to the odd stones
+        let halves_only = Blinker::new(vec![Rule::new("halve", |stone| stone % 2 == 0, |stone| Some(vec![stone / 2]))]);
+        assert_eq!(halves_only.err().unwrap(), "the last rule, halve, has to apply to every stone");
     }
-    
-    // otherwise, etc
-    let size = calculate_size(stone_value * 2024, number_ticks - 1, memo);
-    memo.insert((stone_value, number_ticks), size);
-    
-    size
-}
-
-fn load_stones() -> Vec<u64> {
-    fs::read_to_string("d11/input").unwrap()
-        .split(" ")
-        .map(|n| n.parse().unwrap())
-        .collect()
-}
\ No newline at end of file
+}
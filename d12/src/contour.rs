@@ -0,0 +1,182 @@
+use crate::Point;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The outline of a region, along the edges between plots: a plot at `x,y` covers the square
+/// from `x,y` to `x+1,y+1`. Every loop only has its corners in it, in order; the outer boundary
+/// goes clockwise (on screen, with y going down) and holes go the other way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contour {
+    pub outer: Vec<Point>,
+    pub holes: Vec<Vec<Point>>,
+}
+
+impl Contour {
+    /// Around `plots`, where `inside` says whether a plot is in the same region.
+    pub fn of(plots: &[Point], inside: impl Fn(Point) -> bool) -> Contour {
+        // every edge between a plot and something else, going clockwise around the plot,
+        // by where it starts
+        let mut edges: HashMap<Point, Vec<Point>> = HashMap::new();
+        for plot in plots {
+            let [top_left, top_right, bottom_right, bottom_left] = [(0, 0), (1, 0), (1, 1), (0, 1)]
+                .map(|(dx, dy)| *plot + Point::new(dx, dy));
+            let sides = [
+                ((0, -1), top_left, top_right),
+                ((1, 0), top_right, bottom_right),
+                ((0, 1), bottom_right, bottom_left),
+                ((-1, 0), bottom_left, top_left),
+            ];
+            for ((dx, dy), from, to) in sides {
+                if !inside(*plot + Point::new(dx, dy)) {
+                    edges.entry(from).or_default().push(to);
+                }
+            }
+        }
+
+        let mut loops = vec![];
+        while let Some(&start) = edges.keys().min() {
+            loops.push(trace(&mut edges, start));
+        }
+
+        // the outer boundary is the one that goes around everything else
+        loops.sort_by_key(|corners| std::cmp::Reverse(signed_area(corners)));
+        let outer = loops.remove(0);
+        Contour { outer, holes: loops }
+    }
+
+    fn loops(&self) -> impl Iterator<Item = &Vec<Point>> {
+        std::iter::once(&self.outer).chain(self.holes.iter())
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.loops()
+            .flat_map(|corners| edges_of(corners))
+            .map(|(from, to)| ((to.x - from.x).abs() + (to.y - from.y).abs()) as usize)
+            .sum()
+    }
+
+    /// Every corner is where one side ends and the next starts.
+    pub fn sides(&self) -> usize {
+        self.loops().map(|corners| corners.len()).sum()
+    }
+
+    /// Holes take away from the outer area.
+    pub fn area(&self) -> usize {
+        self.loops().map(|corners| signed_area(corners)).sum::<i64>() as usize
+    }
+
+    /// No holes, and every corner turns the same way; which, with only right angles, means
+    /// it's a rectangle.
+    pub fn is_convex(&self) -> bool {
+        self.holes.is_empty()
+            && (0..self.outer.len()).all(|idx| {
+                let [a, b, c] = [0, 1, 2].map(|offset| self.outer[(idx + offset) % self.outer.len()]);
+                turn(b - a, c - b) > 0
+            })
+    }
+
+    /// An SVG path: each loop is its own subpath, and even-odd filling leaves the holes empty.
+    fn svg_path(&self, scale: i32) -> String {
+        let mut path = String::new();
+        for corners in self.loops() {
+            for (idx, corner) in corners.iter().enumerate() {
+                let command = if idx == 0 { 'M' } else { 'L' };
+                write!(path, "{command}{},{} ", corner.x * scale, corner.y * scale).unwrap();
+            }
+            path.push_str("Z ");
+        }
+        path.trim_end().to_string()
+    }
+}
+
+/// Follows edges from `start` until it gets back there, taking them out of `edges` as it goes.
+/// Where a region touches itself at a corner, there's a choice of edges; turning left keeps
+/// the loop tight around whatever's outside the region, so two holes that touch at a corner
+/// stay two holes.
+fn trace(edges: &mut HashMap<Point, Vec<Point>>, start: Point) -> Vec<Point> {
+    let mut path = vec![start];
+    let mut heading: Option<Point> = None;
+    let mut current = start;
+    loop {
+        let outgoing = edges.get_mut(&current).unwrap();
+        let pick = match heading {
+            Some(heading) => (0..outgoing.len()).min_by_key(|&idx| turn(heading, outgoing[idx] - current)).unwrap(),
+            None => 0,
+        };
+        let next = outgoing.swap_remove(pick);
+        if outgoing.is_empty() {
+            edges.remove(&current);
+        }
+
+        heading = Some(next - current);
+        if next == start {
+            break;
+        }
+        path.push(next);
+        current = next;
+    }
+
+    corners_only(path)
+}
+
+/// Drops the points in the middle of straight runs.
+fn corners_only(path: Vec<Point>) -> Vec<Point> {
+    let len = path.len();
+    (0..len)
+        .filter(|&idx| {
+            let (before, here, after) = (path[(idx + len - 1) % len], path[idx], path[(idx + 1) % len]);
+            turn(here - before, after - here) != 0
+        })
+        .map(|idx| path[idx])
+        .collect()
+}
+
+/// Positive for a right turn (on screen), negative for a left one, zero for straight on.
+fn turn(from: Point, to: Point) -> i32 {
+    (from.x * to.y - from.y * to.x).signum()
+}
+
+fn edges_of(corners: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    (0..corners.len()).map(|idx| (corners[idx], corners[(idx + 1) % corners.len()]))
+}
+
+/// Shoelace formula; clockwise on screen comes out positive.
+fn signed_area(corners: &[Point]) -> i64 {
+    edges_of(corners)
+        .map(|(from, to)| from.x as i64 * to.y as i64 - to.x as i64 * from.y as i64)
+        .sum::<i64>()
+        / 2
+}
+
+/// One filled shape per region, coloured by its letter.
+pub fn to_svg<'a>(contours: impl Iterator<Item = (char, &'a Contour)>, width: usize, height: usize) -> String {
+    const SCALE: i32 = 10;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width as i32 * SCALE,
+        height as i32 * SCALE
+    );
+    for (letter, contour) in contours {
+        // spread the letters around the colour wheel
+        let hue = (letter as u32 * 47) % 360;
+        writeln!(
+            svg,
+            "  <path d=\"{}\" fill=\"hsl({hue}, 65%, 55%)\" fill-rule=\"evenodd\" stroke=\"black\"><title>{}</title></path>",
+            contour.svg_path(SCALE),
+            escape(letter)
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Any letter can be a plot, including the ones that mean something in XML.
+fn escape(letter: char) -> String {
+    match letter {
+        '&' => String::from("&amp;"),
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        _ => letter.to_string(),
+    }
+}
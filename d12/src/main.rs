@@ -1,160 +1,200 @@
-use lib2d::Direction::{Down, Left, Right, Up};
-use lib2d::{dir_delta, Point2d};
-use std::collections::{HashMap, HashSet};
-use std::fs;
+mod contour;
+
+use crate::contour::Contour;
+use lib2d::{label_components, Connectivity, Grid, Point2d};
+use libparse::{ParseResult, Source};
+use std::path::PathBuf;
 
 type Point = Point2d<i32>;
 
 #[derive(Debug)]
 struct Region {
     letter: char,
-    points: HashSet<Point>,
+    points: Vec<Point>,
 }
 
-fn main() {
-    part1();
-    part2();
+/// Every region, by label, along with which region each plot is in.
+struct Regions {
+    labels: Grid<usize>,
+    regions: Vec<Region>,
 }
 
-fn part1() {
-    let map = load_map();
-    let distinct_regions = find_regions(&map);
+impl Regions {
+    fn contour(&self, label: usize) -> Contour {
+        Contour::of(&self.regions[label].points, |point| self.labels.get(point) == Some(&label))
+    }
 
-    println!("Found {:?} distinct regions:", distinct_regions.len());
+    fn contours(&self) -> Vec<Contour> {
+        (0..self.regions.len()).map(|label| self.contour(label)).collect()
+    }
+}
 
-    let cost: usize = distinct_regions
-        .iter()
-        .map(|region| {
-            let area = region.points.len();
-            let perimeter = find_perimeter(&region.points);
+type Map = Grid<char>;
+
+struct Config {
+    // print each region's shape to stderr
+    contours: bool,
+    // draw every region into an SVG
+    svg: Option<PathBuf>,
+}
+
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { contours: false, svg: None };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--contours" => config.contours = true,
+                "--svg" => {
+                    let path = args.next().ok_or("--svg needs a file to write the picture to")?;
+                    config.svg = Some(PathBuf::from(path));
+                }
+                _ => return Err(format!("Unrecognized argument {arg:?}; d12 takes --contours and --svg")),
+            }
+        }
 
-            area * perimeter
-        })
-        .sum();
+        Ok(config)
+    }
+}
 
-    println!("Part 1: {cost}");
+fn main() {
+    let config = Config::from_args(runner::day_args()).unwrap_or_else(|message| runner::bad_day_args("d12", &message));
+    runner::run("d12", load_map, |map| part1(map, &config), part2);
 }
 
-fn find_regions(map: &HashMap<Point, char>) -> Vec<Region> {
-    let mut distinct_regions = vec![];
-    let mut unmatched_points = HashSet::new();
-    unmatched_points.extend(map.keys());
-
-    while !unmatched_points.is_empty() {
-        // grab any point
-        let point = unmatched_points.iter().next().unwrap();
-        let letter = *map.get(point).unwrap();
-        let mut region_points = HashSet::new();
-        paint_region(point, &map, &mut region_points);
-        region_points.iter().for_each(|region_point| {
-            unmatched_points.remove(region_point);
-        });
-
-        distinct_regions.push(Region {
-            letter,
-            points: region_points,
-        });
+fn part1(map: &Map, config: &Config) -> usize {
+    let regions = find_regions(map);
+    let contours = regions.contours();
+
+    if config.contours {
+        for (region, contour) in regions.regions.iter().zip(&contours) {
+            let shape = if contour.is_convex() { ", convex" } else { "" };
+            eprintln!(
+                "{:?} at {:?}: area {}, perimeter {}, {} sides, {} holes{shape}",
+                region.letter, contour.outer[0], contour.area(), contour.perimeter(), contour.sides(), contour.holes.len()
+            );
+        }
+    }
+    if let Some(path) = &config.svg {
+        let letters = regions.regions.iter().map(|region| region.letter);
+        let svg = contour::to_svg(letters.zip(&contours), map.width(), map.height());
+        if let Err(err) = std::fs::write(path, svg) {
+            eprintln!("d12: couldn't write {}: {err}", path.display());
+        }
     }
 
-    distinct_regions
+    contours
+        .iter()
+        .map(|contour| contour.area() * contour.perimeter())
+        .sum()
 }
 
-fn paint_region(point: &Point, map: &HashMap<Point, char>, region: &mut HashSet<Point>) {
-    // we've already done this
-    if region.contains(point) {
-        return;
+/// Plots only join up with the same letter above, below, or beside them.
+fn find_regions(map: &Map) -> Regions {
+    let labels = label_components(map, Connectivity::Four);
+    let mut regions: Vec<Region> = labels.components
+        .iter()
+        .map(|component| Region { letter: component.value, points: Vec::with_capacity(component.area) })
+        .collect();
+    for (point, &label) in labels.grid.iter() {
+        regions[label].points.push(point);
     }
 
-    // we are always in our own region
-    region.insert(point.clone());
+    Regions { labels: labels.grid, regions }
+}
 
-    let own_val = map.get(point).unwrap();
+fn part2(map: &Map) -> usize {
+    find_regions(map)
+        .contours()
+        .iter()
+        .map(|contour| contour.area() * contour.sides())
+        .sum()
+}
 
-    for dir in [Up, Down, Left, Right] {
-        let neighbor = *point + dir_delta(dir);
-        if let Some(neighbor_val) = map.get(&neighbor) {
-            if neighbor_val == own_val {
-                paint_region(&neighbor, map, region);
-            }
+fn load_map(input: &str) -> ParseResult<Map> {
+    let source = Source::new(input);
+    // every plot letter is fair game
+    let mut rows: Vec<Vec<char>> = vec![];
+    for line in input.lines() {
+        let row: Vec<char> = line.chars().collect();
+        if rows.first().is_some_and(|first| first.len() != row.len()) {
+            return Err(source.error(line, format!("a row {} plots wide", rows[0].len())));
         }
+        rows.push(row);
     }
-}
 
-fn find_perimeter(points: &HashSet<Point>) -> usize {
-    points.iter()
-        .map(|point| {
-            let mut edges: usize = 0;
-            // for each direction, if the region does not contain another point in that direction,
-            // that direction counts as an edge
-            for dir in [Up, Down, Left, Right] {
-                if !points.contains(&(*point + dir_delta(dir))) {
-                    edges += 1;
-                }
-            }
-            edges
-        })
-        .sum()
+    Ok(Grid::from_rows(rows).unwrap())
 }
 
-fn part2() {
-    let map = load_map();
-    let regions = find_regions(&map);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let cost: usize = regions.iter()
-        .map(|region| {
-            let size = region.points.len();
-            let vertices = count_vertices(&region.points);
+    const SMALL: &str = "AAAA\nBBCD\nBBCC\nEEEC\n";
+    const LARGE: &str = "RRRRIICCFF\nRRRRIICCCF\nVVRRRCCFFF\nVVRCCCJFFF\nVVVVCJJCFE\nVVIVCCJJEE\nVVIIICJJEE\nMIIIIIJJEE\nMIIISIJEEE\nMMMISSJEEE\n";
+    // two B holes that touch at a corner
+    const PINCHED: &str = "AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA\n";
 
-            println!("Region {:?}: {size} x {vertices}: {:?}", region.letter, region.points);
-            size * vertices
-        })
-        .sum();
+    fn config() -> Config {
+        Config { contours: false, svg: None }
+    }
 
-    println!("Part 2: {cost}");
-}
+    fn contour_of(input: &str, letter: char) -> Contour {
+        let regions = find_regions(&load_map(input).unwrap());
+        let label = regions.regions.iter().position(|region| region.letter == letter).unwrap();
+        regions.contour(label)
+    }
 
-fn count_vertices(points: &HashSet<Point>) -> usize {
-    let corner_dirs = [(Up, Left), (Up, Right), (Down, Left), (Down, Right)];
-    points.iter()
-        .map(|point| {
-            let mut corners = 0;
+    #[test]
+    fn examples() {
+        let small = load_map(SMALL).unwrap();
+        assert_eq!(part1(&small, &config()), 140);
+        assert_eq!(part2(&small), 80);
 
-            for (d1, d2) in corner_dirs.iter() {
-                let neighbor1 = *point + dir_delta(*d1);
-                let neighbor2 = *point + dir_delta(*d2);
+        let large = load_map(LARGE).unwrap();
+        assert_eq!(part1(&large, &config()), 1930);
+        assert_eq!(part2(&large), 1206);
 
-                // if a point is alone in both an up/down direction and a left/right direction,
-                // that means that it's a convex corner in that diagonal
-                if !points.contains(&neighbor1) && !points.contains(&neighbor2) {
-                    corners += 1;
-                }
-                
-                // if a point has neighbors in both the up/down + left/right direction, but 
-                // no neighbor in that diagonal, it's a concave corner ini that diagonal
-                let diag = *point + dir_delta(*d1) + dir_delta(*d2);
-                if points.contains(&neighbor1) && points.contains(&neighbor2) 
-                    && !points.contains(&diag) {
-                    corners += 1
-                }
-            }
+        assert_eq!(part2(&load_map(PINCHED).unwrap()), 368);
+    }
 
-            corners
-        })
-        .sum()
-}
+    #[test]
+    fn one_huge_region() {
+        let input = format!("{}\n", vec!["A".repeat(500); 500].join("\n"));
+        let map = load_map(&input).unwrap();
+        assert_eq!(find_regions(&map).regions.len(), 1);
+        assert_eq!(part2(&map), 500 * 500 * 4);
+    }
 
-fn load_map() -> HashMap<Point, char> {
-    let mut map = HashMap::new();
+    #[test]
+    fn holes() {
+        let contour = contour_of(PINCHED, 'A');
+        assert_eq!(contour.outer, vec![Point::new(0, 0), Point::new(6, 0), Point::new(6, 6), Point::new(0, 6)]);
+        assert_eq!(contour.holes.len(), 2);
+        assert_eq!(contour.area(), 28);
+        assert_eq!(contour.perimeter(), 40);
+        assert_eq!(contour.sides(), 12);
+        assert!(!contour.is_convex());
+    }
 
-    fs::read_to_string("d12/input")
-        .unwrap()
-        .lines()
-        .enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate().for_each(|(x, char)| {
-                map.insert(Point2d::new(x as i32, y as i32), char);
-            });
-        });
+    #[test]
+    fn convexity() {
+        assert!(contour_of(SMALL, 'A').is_convex());
+        assert!(contour_of(SMALL, 'B').is_convex());
+        let c = contour_of(SMALL, 'C');
+        assert!(!c.is_convex());
+        assert_eq!((c.area(), c.perimeter(), c.sides()), (4, 10, 8));
+    }
 
-    map
+    #[test]
+    fn svg() {
+        let contour = contour_of(SMALL, 'D');
+        let svg = contour::to_svg([('D', &contour)].into_iter(), 4, 4);
+        assert!(svg.contains("d=\"M30,10 L40,10 L40,20 L30,20 Z\""));
+
+        let svg = contour::to_svg([('<', &contour), ('&', &contour)].into_iter(), 4, 4);
+        assert!(svg.contains("<title>&lt;</title>"));
+        assert!(svg.contains("<title>&amp;</title>"));
+    }
 }
@@ -1,128 +1,263 @@
-use lazy_static::lazy_static;
+mod solve;
+
+use crate::solve::Pricing;
 use lib2d::Point2d;
-use regex::Regex;
-use std::fs;
-
-lazy_static! {
-    pub static ref MACHINE_DEF: Regex = Regex::new(
-        r"Button A: X\+(?<ax>\d+), Y\+(?<ay>\d+)
-Button B: X\+(?<bx>\d+), Y\+(?<by>\d+)
-Prize: X=(?<px>\d+), Y=(?<py>\d+)"
-    )
-    .unwrap();
-}
+use libparse::{ParseResult, Source};
 
 type Point = Point2d<i64>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+struct Button {
+    name: String,
+    delta: Point,
+}
+
+#[derive(Clone, Debug)]
 struct Machine {
-    a: Point,
-    b: Point,
+    buttons: Vec<Button>,
     prize: Point,
 }
 
+struct Config {
+    // what pressing each button costs, in the order they're listed
+    costs: Vec<i128>,
+    // part 1's most presses of any one button
+    limit: i128,
+    // print how each machine went to stderr
+    explain: bool,
+}
+
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { costs: vec![3, 1], limit: 100, explain: false };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--costs" => {
+                    let spec = args.next().ok_or("--costs needs what each button costs, like 3,1")?;
+                    config.costs = spec.split(',')
+                        .map(|cost| cost.parse().ok().filter(|&cost| cost >= 0))
+                        .collect::<Option<_>>()
+                        .ok_or(format!("Expected costs of at least 0, like 3,1, not {spec:?}"))?;
+                }
+                "--limit" => {
+                    let limit = args.next().ok_or("--limit needs a number of presses")?;
+                    config.limit = limit.parse()
+                        .ok()
+                        .filter(|&limit| limit >= 0)
+                        .ok_or(format!("Expected a number of presses, not {limit:?}"))?;
+                }
+                "--explain" => config.explain = true,
+                _ => return Err(format!("Unrecognized argument {arg:?}; d13 takes --costs, --limit, and --explain")),
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn pricing(&self, limit: Option<i128>) -> Pricing {
+        Pricing { costs: self.costs.clone(), limit }
+    }
+}
+
 fn main() {
-    part1();
-    part2();
+    let config = Config::from_args(runner::day_args()).unwrap_or_else(|message| runner::bad_day_args("d13", &message));
+    runner::run("d13", read_machines, |machines| part1(machines, &config), |machines| part2(machines, &config));
 }
 
-fn part1() {
-    let machines = read_machines(0);
-    println!("Got {:?} machines", machines.len());
-    let cost: usize = machines
+fn total_cost(machines: &[Machine], pricing: &Pricing, explain: bool) -> i128 {
+    machines
         .iter()
-        .map(get_working_counts)
-        .flat_map(|list| list.iter().map(|(a, b)| a * 3 + b * 1).min())
-        .sum();
+        .enumerate()
+        .map(|(idx, machine)| {
+            let solved = solve::solve(machine, pricing);
+            if explain {
+                match &solved {
+                    Ok(presses) => {
+                        let counts: Vec<String> = machine.buttons.iter()
+                            .zip(&presses.counts)
+                            .map(|(button, count)| format!("{} x{count}", button.name))
+                            .collect();
+                        eprintln!("Machine {}: {} for {} tokens", idx + 1, counts.join(", "), presses.cost);
+                    }
+                    Err(why) => eprintln!("Machine {}: can't be won; {why}", idx + 1),
+                }
+            }
+            solved.map_or(0, |presses| presses.cost)
+        })
+        .sum()
+}
 
-    println!("Part 1: {cost}");
+fn part1(machines: &[Machine], config: &Config) -> i128 {
+    total_cost(machines, &config.pricing(Some(config.limit)), config.explain)
 }
 
-fn part2() {
+fn part2(machines: &[Machine], config: &Config) -> i128 {
     let big_time: i64 = 10000000000000;
-    let machines = read_machines(big_time);
+    let offset = Point2d::new(big_time, big_time);
 
-    let cost: i64 = machines
+    let moved: Vec<Machine> = machines
         .iter()
-        .flat_map(|machine| {
-            // wait a minute, this is just ALGEBRA!
-            let numerator = (machine.prize.x * machine.b.y) - (machine.b.x * machine.prize.y);
-            let denominator = (machine.a.x * machine.b.y) - (machine.b.x * machine.a.y);
-
-            if numerator % denominator != 0 {
-                return None;
-            }
-
-            let a_count = numerator / denominator;
+        .map(|machine| Machine { prize: machine.prize + offset, ..machine.clone() })
+        .collect();
+    total_cost(&moved, &config.pricing(None), config.explain)
+}
 
-            let b_numerator = machine.prize.x - (machine.a.x * a_count);
+/// Any number of `Button X: ...` lines, then the prize.
+fn read_machines(input: &str) -> ParseResult<Vec<Machine>> {
+    let source = Source::new(input);
+    let xy = |value: &str| {
+        let [x, y] = source.integers_exact(value, "an X and Y")?;
+        Ok(Point2d::new(x, y))
+    };
 
-            if b_numerator % machine.b.x != 0 {
-                // apparently this is fine
-                println!(
-                    "I got an a_count of {a_count}, but b_count was non integer! {:?}",
-                    machine
-                );
-                return None;
+    source
+        .sections(input)
+        .map(|section| {
+            let lines: Vec<&str> = section.lines().collect();
+            let (prize, button_lines) = lines.split_last().unwrap();
+            if button_lines.is_empty() {
+                return Err(source.error(prize, "a \"Button X: ...\" line"));
             }
 
-            let b_count = b_numerator / machine.b.x;
-            let test_point = machine.a * a_count + machine.b * b_count;
-            if test_point != machine.prize {
-                panic!(
-                    "I thought A={a_count} B={b_count} would give me {:?}, but I got {:?}",
-                    machine.prize, test_point
-                );
+            let mut buttons: Vec<Button> = vec![];
+            for line in button_lines {
+                let (key, value) = source.key_value(line)?;
+                let name = match key.strip_prefix("Button ") {
+                    Some(name) if !name.is_empty() && buttons.iter().all(|button| button.name != name) => name,
+                    _ => return Err(source.error(key, "\"Button\" and a name no other button has")),
+                };
+                buttons.push(Button { name: name.to_string(), delta: xy(value)? });
             }
 
-            Some((a_count, b_count))
+            Ok(Machine { buttons, prize: xy(source.field(prize, "Prize")?)? })
         })
-        .map(|(a_count, b_count)| a_count * 3 + b_count)
-        .sum();
-
-    println!("Part 2: {cost}");
+        .collect()
 }
 
-fn get_working_counts(machine: &Machine) -> Vec<(usize, usize)> {
-    let mut working_counts = vec![];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solve::Infeasible;
 
-    for a_count in 0..100 {
-        let a_offset = machine.a * a_count;
-        if a_offset.x > machine.prize.x || a_offset.y > machine.prize.y {
-            break;
-        }
-        for b_count in 0..100 {
-            let total_offset = a_offset + machine.b * b_count;
-            if total_offset == machine.prize {
-                working_counts.push((a_count as usize, b_count as usize));
-                break;
-            }
+    #[test]
+    fn reads_machines() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n";
+        let machines = read_machines(input).unwrap();
+        assert_eq!(machines.len(), 1);
+        assert_eq!(machines[0].prize, Point2d::new(8400, 5400));
+    }
 
-            if total_offset.x > machine.prize.x || total_offset.y > machine.prize.y {
-                break;
-            }
-        }
+    const EXAMPLE: &str = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+                           Button A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176\n\n\
+                           Button A: X+17, Y+86\nButton B: X+84, Y+37\nPrize: X=7870, Y=6450\n\n\
+                           Button A: X+69, Y+23\nButton B: X+27, Y+71\nPrize: X=18641, Y=10279\n";
+
+    fn machine(buttons: &[(i64, i64)], prize: (i64, i64)) -> Machine {
+        let buttons = buttons.iter()
+            .zip('A'..)
+            .map(|(&(x, y), name)| Button { name: name.to_string(), delta: Point2d::new(x, y) })
+            .collect();
+        Machine { buttons, prize: Point2d::new(prize.0, prize.1) }
     }
 
-    working_counts
-}
+    fn presses(machine: &Machine, costs: &[i128], limit: Option<i128>) -> Result<Vec<i128>, Infeasible> {
+        solve::solve(machine, &Pricing { costs: costs.to_vec(), limit }).map(|presses| presses.counts)
+    }
 
-fn read_machines(prize_offset: i64) -> Vec<Machine> {
-    MACHINE_DEF
-        .captures_iter(&fs::read_to_string("d13/input").unwrap())
-        .map(|caps| {
-            let ax = caps["ax"].parse().unwrap();
-            let ay = caps["ay"].parse().unwrap();
-            let bx = caps["bx"].parse().unwrap();
-            let by = caps["by"].parse().unwrap();
-            let px: i64 = caps["px"].parse().unwrap();
-            let py: i64 = caps["py"].parse().unwrap();
-
-            Machine {
-                a: Point2d::new(ax, ay),
-                b: Point2d::new(bx, by),
-                prize: Point2d::new(px + prize_offset, py + prize_offset),
-            }
-        })
-        .collect()
+    #[test]
+    fn example() {
+        let machines = read_machines(EXAMPLE).unwrap();
+        let config = Config::from_args(vec![]).unwrap();
+        assert_eq!(part1(&machines, &config), 480);
+        // only the second and fourth can be won
+        let won: Vec<bool> = machines.iter()
+            .map(|machine| Machine { prize: machine.prize + Point2d::new(10000000000000, 10000000000000), ..machine.clone() })
+            .map(|machine| solve::solve(&machine, &config.pricing(None)).is_ok())
+            .collect();
+        assert_eq!(won, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn collinear_buttons() {
+        let doubled = machine(&[(1, 1), (2, 2)], (10, 10));
+        assert_eq!(presses(&doubled, &[3, 1], None), Ok(vec![0, 5]));
+        assert_eq!(presses(&doubled, &[1, 3], None), Ok(vec![10, 0]));
+        assert_eq!(presses(&doubled, &[3, 1], Some(4)), Ok(vec![2, 4]));
+        assert_eq!(presses(&machine(&[(1, 1), (2, 2)], (5, 5)), &[3, 1], None), Ok(vec![1, 2]));
+
+        assert_eq!(presses(&machine(&[(2, 2), (4, 4)], (5, 5)), &[3, 1], None), Err(Infeasible::OffLattice));
+        assert_eq!(presses(&machine(&[(1, 1), (2, 2)], (5, 6)), &[3, 1], None), Err(Infeasible::OffLattice));
+        assert_eq!(presses(&machine(&[(3, 6), (0, 0)], (9, 18)), &[3, 1], None), Ok(vec![3, 0]));
+        assert_eq!(presses(&machine(&[(0, 0), (0, 0)], (0, 0)), &[3, 1], None), Ok(vec![0, 0]));
+        // the limit rules out the only way
+        assert_eq!(
+            presses(&machine(&[(1, 0), (0, 1)], (5, 200)), &[3, 1], Some(100)),
+            Err(Infeasible::OutOfReach { limit: Some(100) })
+        );
+    }
+
+    #[test]
+    fn more_buttons() {
+        let input = "Button A: X+3, Y+1\nButton B: X+1, Y+3\nButton C: X+1, Y+1\nPrize: X=10, Y=10\n";
+        let machines = read_machines(input).unwrap();
+        assert_eq!(machines[0].buttons.len(), 3);
+        assert_eq!(presses(&machines[0], &[3, 3, 1], None), Ok(vec![0, 0, 10]));
+        assert_eq!(presses(&machines[0], &[1, 1, 1], None), Ok(vec![2, 2, 2]));
+        assert_eq!(presses(&machines[0], &[1, 1, 1], Some(1)), Err(Infeasible::OutOfReach { limit: Some(1) }));
+        assert_eq!(
+            presses(&machines[0], &[1, 1], None),
+            Err(Infeasible::Unpriced { button: String::from("C") })
+        );
+
+        // everything moves an even number of steps
+        assert_eq!(presses(&machine(&[(2, 0), (0, 2), (2, 2)], (3, 4)), &[1, 1, 1], None), Err(Infeasible::OffLattice));
+        // it can't be done without going backwards
+        assert_eq!(
+            presses(&machine(&[(1, 0), (2, 0), (0, -1)], (5, 5)), &[1, 1, 1], None),
+            Err(Infeasible::OutOfReach { limit: None })
+        );
+
+        // backwards buttons with no limit, where only the costs stop them going on forever
+        let wandering = machine(&[(-2, 3), (-1, -2), (4, 3), (3, -1)], (2, 7));
+        assert_eq!(presses(&wandering, &[0, 3, 2, 3], None), Ok(vec![4, 10, 5, 0]));
+        assert_eq!(presses(&wandering, &[0, 3, 2, 3], Some(10)), Ok(vec![4, 10, 5, 0]));
+        let around = machine(&[(1, 0), (0, 1), (-1, -1)], (3, 4));
+        assert_eq!(presses(&around, &[1, 1, 1], None), Ok(vec![3, 4, 0]));
+
+        // buttons that cost nothing could cancel each other out forever
+        assert_eq!(
+            presses(&around, &[0, 0, 0], None),
+            Err(Infeasible::Unbounded { button: String::from("A") })
+        );
+        assert_eq!(presses(&around, &[0, 0, 0], Some(5)), Ok(vec![3, 4, 0]));
+        let back_and_forth = machine(&[(0, 1), (1, 0), (-1, 0)], (2, 3));
+        assert_eq!(
+            presses(&back_and_forth, &[1, 0, 0], None),
+            Err(Infeasible::Unbounded { button: String::from("B") })
+        );
+
+        // way out there, where brute force won't do
+        let far = machine(&[(94, 34), (22, 67), (17, 86)], (10000000008400, 10000000005400));
+        let counts = presses(&far, &[3, 1, 2], None).unwrap();
+        let landed = (0..3).fold((0, 0), |(x, y), i| {
+            let delta = far.buttons[i].delta;
+            (x + counts[i] * delta.x as i128, y + counts[i] * delta.y as i128)
+        });
+        assert_eq!(landed, (10000000008400, 10000000005400));
+    }
+
+    #[test]
+    fn broken_machine() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+                     Button A: X+26, Y+66\nButton B: X+67\nPrize: X=12748, Y=12176\n";
+        let err = read_machines(input).unwrap_err();
+        assert_eq!((err.line, err.column), (6, 15));
+        assert_eq!(err.expected, "2 of an X and Y");
+        assert_eq!(err.found, None);
+
+        let err = read_machines("Prize: X=1, Y=2\n").unwrap_err();
+        assert_eq!(err.expected, "a \"Button X: ...\" line");
+    }
 }
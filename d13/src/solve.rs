@@ -0,0 +1,465 @@
+use crate::Machine;
+use num::rational::Ratio;
+use std::fmt::{Display, Formatter};
+
+/// What pressing each button costs, in the order the machine lists them, and how many times
+/// any one button can be pressed. Costs can't be negative, or there'd be no cheapest way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pricing {
+    pub costs: Vec<i128>,
+    pub limit: Option<i128>,
+}
+
+/// How many times to press each button, in the order the machine lists them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Presses {
+    pub counts: Vec<i128>,
+    pub cost: i128,
+}
+
+/// Why a machine can't be won.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Infeasible {
+    /// There are more buttons than costs
+    Unpriced { button: String },
+    /// Not even pressing buttons backwards would get there
+    OffLattice,
+    /// It can only be done by pressing something backwards, or too many times
+    OutOfReach { limit: Option<i128> },
+    /// Buttons that cost nothing can undo each other forever, so with no limit there's no
+    /// telling when to stop looking
+    Unbounded { button: String },
+}
+
+impl Display for Infeasible {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Infeasible::Unpriced { button } => write!(f, "there's no cost for button {button}"),
+            Infeasible::OffLattice => write!(f, "no whole number of presses lands on the prize"),
+            Infeasible::OutOfReach { limit: None } => {
+                write!(f, "every way to land on the prize presses some button a negative number of times")
+            }
+            Infeasible::OutOfReach { limit: Some(limit) } => write!(
+                f,
+                "every way to land on the prize presses some button a negative number of times, or more than {limit}"
+            ),
+            Infeasible::Unbounded { button } => {
+                write!(f, "button {button} can go round in a loop of free presses forever, so it needs a press limit")
+            }
+        }
+    }
+}
+
+type Vector = (i128, i128);
+
+fn vector(point: lib2d::Point2d<i64>) -> Vector {
+    (point.x as i128, point.y as i128)
+}
+
+fn cross(u: Vector, v: Vector) -> i128 {
+    u.0 * v.1 - u.1 * v.0
+}
+
+/// The cheapest presses that land the claw right on the prize.
+pub fn solve(machine: &Machine, pricing: &Pricing) -> Result<Presses, Infeasible> {
+    if let Some(button) = machine.buttons.get(pricing.costs.len()) {
+        return Err(Infeasible::Unpriced { button: button.name.clone() });
+    }
+    let buttons: Vec<Vector> = machine.buttons.iter().map(|button| vector(button.delta)).collect();
+    let prize = vector(machine.prize);
+    if !on_lattice(&buttons, prize) {
+        return Err(Infeasible::OffLattice);
+    }
+
+    let costs = &pricing.costs[..buttons.len()];
+    let counts = match buttons[..] {
+        [a, b] => solve_pair(a, b, prize, (costs[0], costs[1]), pricing.limit).map(|(a, b)| vec![a, b]),
+        _ => {
+            let mut search = BranchAndBound::new(&buttons, prize, costs, press_bounds(&buttons, prize, pricing.limit));
+            // branching on a button with no bound might never finish
+            if let Some(button) = free_loop(&buttons, costs, &search.bounds) {
+                return Err(Infeasible::Unbounded { button: machine.buttons[button].name.clone() });
+            }
+            search.bound_by_cost();
+            search.solve()
+        }
+    };
+
+    match counts {
+        Some(counts) => {
+            let cost = counts.iter().zip(costs).map(|(count, cost)| count * cost).sum();
+            Ok(Presses { counts, cost })
+        }
+        None => Err(Infeasible::OutOfReach { limit: pricing.limit }),
+    }
+}
+
+/// Whether some whole numbers of presses, negative ones included, add up to `target`. Column
+/// operations bring the buttons down to Hermite normal form: one column that has everything
+/// the x's can do, and then a gcd of what's left over in y once the x's are out of the way.
+fn on_lattice(buttons: &[Vector], target: Vector) -> bool {
+    let mut pivot: Option<Vector> = None;
+    let mut y_only = 0;
+    for &button in buttons {
+        let leftover = match pivot {
+            _ if button.0 == 0 => button,
+            None => {
+                pivot = Some(button);
+                continue;
+            }
+            Some(current) => {
+                // a unimodular swap of the two columns, leaving all the x in the pivot
+                let (gcd, u, v) = extended_gcd(current.0, button.0);
+                pivot = Some((gcd, u * current.1 + v * button.1));
+                (0, (button.0 / gcd) * current.1 - (current.0 / gcd) * button.1)
+            }
+        };
+        y_only = extended_gcd(y_only, leftover.1).0;
+    }
+
+    let y_left = match pivot {
+        Some((x, y)) if target.0 % x == 0 => target.1 - (target.0 / x) * y,
+        None if target.0 == 0 => target.1,
+        _ => return false,
+    };
+    if y_only == 0 {
+        y_left == 0
+    } else {
+        y_left % y_only == 0
+    }
+}
+
+/// The most times each button could be worth pressing. Along an axis no button moves the claw
+/// backwards on, a button can't go forwards more times than it takes to pass the prize; and a
+/// button that doesn't move the claw at all is never worth pressing, since costs aren't
+/// negative. Anything else only has the limit, if there is one.
+fn press_bounds(buttons: &[Vector], prize: Vector, limit: Option<i128>) -> Vec<Option<i128>> {
+    let axes: [fn(Vector) -> i128; 2] = [|v| v.0, |v| v.1];
+    let forwards_only: Vec<bool> = axes.iter().map(|axis| buttons.iter().all(|&button| axis(button) >= 0)).collect();
+
+    buttons.iter()
+        .map(|&button| {
+            if button == (0, 0) {
+                return Some(0);
+            }
+            axes.iter()
+                .zip(&forwards_only)
+                .filter(|&(axis, &forwards)| forwards && axis(button) > 0)
+                .map(|(axis, _)| div_floor(axis(prize), axis(button)))
+                .chain(limit)
+                .min()
+        })
+        .collect()
+}
+
+/// A button that can be pressed along with some others, as often as you like, for nothing,
+/// and leave the claw where it started. In 2D, if any presses of some buttons add up to
+/// nothing, then one button pointing straight back at another does, or three that surround
+/// the start do.
+fn free_loop(buttons: &[Vector], costs: &[i128], bounds: &[(i128, Option<i128>)]) -> Option<usize> {
+    let free: Vec<usize> = (0..buttons.len())
+        .filter(|&i| costs[i] == 0 && bounds[i].1.is_none() && buttons[i] != (0, 0))
+        .collect();
+    let dot = |u: Vector, v: Vector| u.0 * v.0 + u.1 * v.1;
+
+    for (k, &i) in free.iter().enumerate() {
+        for (l, &j) in free.iter().enumerate().skip(k + 1) {
+            let (u, v) = (buttons[i], buttons[j]);
+            if cross(u, v) == 0 && dot(u, v) < 0 {
+                return Some(i);
+            }
+            for &w in free[l + 1..].iter().map(|&m| &buttons[m]) {
+                let turns = [cross(u, v), cross(v, w), cross(w, u)].map(i128::signum);
+                if turns[0] != 0 && turns.iter().all(|&turn| turn == turns[0]) {
+                    return Some(i);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Two buttons, whose presses can be worked out directly.
+fn solve_pair(a: Vector, b: Vector, prize: Vector, costs: (i128, i128), limit: Option<i128>) -> Option<(i128, i128)> {
+    // Cramer's rule: two independent buttons only ever have the one answer
+    let det = cross(a, b);
+    if det != 0 {
+        let (a_presses, b_presses) = (cross(prize, b), cross(a, prize));
+        if a_presses % det != 0 || b_presses % det != 0 {
+            return None;
+        }
+        let presses = (a_presses / det, b_presses / det);
+        let allowed = |count: i128| count >= 0 && limit.is_none_or(|limit| count <= limit);
+        return (allowed(presses.0) && allowed(presses.1)).then_some(presses);
+    }
+
+    solve_collinear(a, b, prize, costs, limit)
+}
+
+/// Both buttons move the claw along the same line, so it's one equation in two unknowns:
+/// `alpha * a + beta * b = target`, counting in steps along the line. The answers are a 1-D
+/// family, and the cost changes steadily along it, so the cheapest is at one end or the other.
+fn solve_collinear(a: Vector, b: Vector, prize: Vector, costs: (i128, i128), limit: Option<i128>) -> Option<(i128, i128)> {
+    let Some(step) = [a, b].into_iter().find(|&v| v != (0, 0)).map(primitive) else {
+        // neither button does anything
+        return (prize == (0, 0)).then_some((0, 0));
+    };
+    if cross(step, prize) != 0 {
+        return None;
+    }
+
+    let along = |v: Vector| if step.0 != 0 { v.0 / step.0 } else { v.1 / step.1 };
+    let (alpha, beta, target) = (along(a), along(b), along(prize));
+    let (gcd, x, y) = extended_gcd(alpha, beta);
+    if target % gcd != 0 {
+        return None;
+    }
+
+    // every answer is a = a0 + da * t, b = b0 + db * t for some whole t
+    let (a0, b0) = (x * (target / gcd), y * (target / gcd));
+    let (da, db) = (beta / gcd, -alpha / gcd);
+    let mut range = Range::default();
+    for (base, delta) in [(a0, da), (b0, db)] {
+        range.at_least(base, delta, 0)?;
+        if let Some(limit) = limit {
+            range.at_most(base, delta, limit)?;
+        }
+    }
+
+    let slope = costs.0 * da + costs.1 * db;
+    // with no negative costs, the cheap end is never the open one
+    let t = match (range.low, range.high) {
+        (Some(low), Some(high)) if low > high => return None,
+        (Some(low), _) if slope >= 0 => low,
+        (_, Some(high)) => high,
+        (low, high) => unreachable!("no cheapest press along {low:?}..{high:?}, sloping {slope}"),
+    };
+
+    Some((a0 + da * t, b0 + db * t))
+}
+
+/// The smallest whole step in the same direction.
+fn primitive(v: Vector) -> Vector {
+    let gcd = extended_gcd(v.0, v.1).0;
+    (v.0 / gcd, v.1 / gcd)
+}
+
+/// `(g, x, y)` with `a * x + b * y = g`, and `g` the gcd, which is never negative.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Whole numbers t, open-ended until something says otherwise.
+#[derive(Default)]
+struct Range {
+    low: Option<i128>,
+    high: Option<i128>,
+}
+
+impl Range {
+    /// Keeps only the t with `base + delta * t >= min`. None if that can't ever happen.
+    fn at_least(&mut self, base: i128, delta: i128, min: i128) -> Option<()> {
+        let needed = min - base;
+        match delta.signum() {
+            0 => (needed <= 0).then_some(()),
+            1 => {
+                let low = div_ceil(needed, delta);
+                self.low = Some(self.low.map_or(low, |current| current.max(low)));
+                Some(())
+            }
+            _ => {
+                let high = div_floor(needed, delta);
+                self.high = Some(self.high.map_or(high, |current| current.min(high)));
+                Some(())
+            }
+        }
+    }
+
+    fn at_most(&mut self, base: i128, delta: i128, max: i128) -> Option<()> {
+        self.at_least(-base, -delta, -max)
+    }
+}
+
+fn div_floor(n: i128, d: i128) -> i128 {
+    let quotient = n / d;
+    if n % d != 0 && (n < 0) != (d < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+fn div_ceil(n: i128, d: i128) -> i128 {
+    -div_floor(-n, d)
+}
+
+type Fraction = Ratio<i128>;
+
+/// Any number of buttons: relax to fractional presses, where the cheapest answer is always at a
+/// corner of what's allowed, then split on a fractional press (at most its floor, or at least
+/// its ceiling) until everything's whole. Anything whose relaxation already costs at least as
+/// much as the best whole answer so far gets dropped.
+struct BranchAndBound<'a> {
+    buttons: &'a [Vector],
+    prize: Vector,
+    costs: &'a [i128],
+    // sets of buttons that can reach anywhere the buttons together can
+    bases: Vec<Vec<usize>>,
+    // every button's presses, from..=to (or on forever)
+    bounds: Vec<(i128, Option<i128>)>,
+    best: Option<(i128, Vec<i128>)>,
+}
+
+impl<'a> BranchAndBound<'a> {
+    fn new(buttons: &'a [Vector], prize: Vector, costs: &'a [i128], limits: Vec<Option<i128>>) -> BranchAndBound<'a> {
+        let pairs: Vec<Vec<usize>> = (0..buttons.len())
+            .flat_map(|i| (i + 1..buttons.len()).map(move |j| vec![i, j]))
+            .filter(|pair| cross(buttons[pair[0]], buttons[pair[1]]) != 0)
+            .collect();
+        let bases = if !pairs.is_empty() {
+            pairs
+        } else if buttons.iter().any(|&button| button != (0, 0)) {
+            (0..buttons.len()).filter(|&i| buttons[i] != (0, 0)).map(|i| vec![i]).collect()
+        } else {
+            vec![vec![]]
+        };
+
+        BranchAndBound { buttons, prize, costs, bases, bounds: limits.into_iter().map(|limit| (0, limit)).collect(), best: None }
+    }
+
+    /// Gives every button with no bound yet one, now that no free loop can keep the search going
+    /// forever. With only two equations, some cheapest whole answer is never more than
+    /// `2 * (4 * biggest + 1)²` presses (all told) away from the cheapest fractional one, where
+    /// `biggest` is the furthest any button moves the claw along either axis. So that much past
+    /// the fractional cost is as much as the best answer can cost, and no button can be pressed
+    /// more times than that pays for; a free button can't get further than that many presses
+    /// from where it was.
+    fn bound_by_cost(&mut self) {
+        let Some((cost, presses)) = self.relax() else {
+            return;
+        };
+        let biggest = self.buttons.iter().map(|button| button.0.abs().max(button.1.abs())).max().unwrap_or(0);
+        let reach = 2 * (4 * biggest + 1).pow(2);
+        let ceiling = cost.ceil().to_integer() + reach * self.costs.iter().max().unwrap_or(&0);
+
+        for (i, (_, to)) in self.bounds.iter_mut().enumerate() {
+            if to.is_none() {
+                *to = Some(match self.costs[i] {
+                    0 => presses[i].floor().to_integer() + reach,
+                    cost => ceiling / cost,
+                });
+            }
+        }
+    }
+
+    fn solve(mut self) -> Option<Vec<i128>> {
+        self.branch();
+        self.best.map(|(_, counts)| counts)
+    }
+
+    fn branch(&mut self) {
+        let Some((cost, presses)) = self.relax() else {
+            return;
+        };
+        if self.best.as_ref().is_some_and(|(best, _)| cost.ceil().to_integer() >= *best) {
+            return;
+        }
+
+        let Some(split) = presses.iter().position(|count| !count.is_integer()) else {
+            let counts = presses.iter().map(|count| count.to_integer()).collect();
+            self.best = Some((cost.to_integer(), counts));
+            return;
+        };
+
+        let floor = presses[split].floor().to_integer();
+        let (from, to) = self.bounds[split];
+        self.bounds[split] = (from, Some(floor));
+        self.branch();
+        self.bounds[split] = (floor + 1, to);
+        self.branch();
+        self.bounds[split] = (from, to);
+    }
+
+    /// The cheapest fractional presses within the bounds: try every corner, which is a basis
+    /// soaking up whatever's left once everything else sits at one of its bounds.
+    fn relax(&self) -> Option<(Fraction, Vec<Fraction>)> {
+        if self.bounds.iter().any(|&(from, to)| to.is_some_and(|to| from > to)) {
+            return None;
+        }
+
+        let mut best: Option<(Fraction, Vec<Fraction>)> = None;
+        for basis in &self.bases {
+            let others: Vec<usize> = (0..self.buttons.len()).filter(|i| !basis.contains(i)).collect();
+            // each of the others either at its lowest or, if it has one, its highest
+            let mut choices = vec![false; others.len()];
+            loop {
+                if let Some(presses) = self.corner(basis, &others, &choices) {
+                    let cost = presses.iter().zip(self.costs).map(|(count, &cost)| *count * cost).sum();
+                    if best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                        best = Some((cost, presses));
+                    }
+                }
+
+                // next combination, counting in binary but skipping the ones with no highest
+                let Some(flip) = (0..others.len()).find(|&k| !choices[k] && self.bounds[others[k]].1.is_some()) else {
+                    break;
+                };
+                choices[flip] = true;
+                choices[..flip].iter_mut().for_each(|choice| *choice = false);
+            }
+        }
+
+        best
+    }
+
+    fn corner(&self, basis: &[usize], others: &[usize], at_high: &[bool]) -> Option<Vec<Fraction>> {
+        let mut presses = vec![Fraction::from_integer(0); self.buttons.len()];
+        let mut left = self.prize;
+        for (&other, &high) in others.iter().zip(at_high) {
+            let (from, to) = self.bounds[other];
+            let count = if high { to.unwrap() } else { from };
+            presses[other] = Fraction::from_integer(count);
+            left = (left.0 - count * self.buttons[other].0, left.1 - count * self.buttons[other].1);
+        }
+
+        match *basis {
+            [] if left != (0, 0) => return None,
+            [] => {}
+            [i] => {
+                let button = self.buttons[i];
+                if cross(button, left) != 0 {
+                    return None;
+                }
+                let dot = |v: Vector| v.0 * button.0 + v.1 * button.1;
+                presses[i] = Fraction::new(dot(left), dot(button));
+            }
+            [i, j] => {
+                let det = cross(self.buttons[i], self.buttons[j]);
+                presses[i] = Fraction::new(cross(left, self.buttons[j]), det);
+                presses[j] = Fraction::new(cross(self.buttons[i], left), det);
+            }
+            _ => unreachable!("a basis in 2D has at most two buttons"),
+        }
+
+        let within = |i: usize| {
+            let (from, to) = self.bounds[i];
+            presses[i] >= Fraction::from_integer(from) && to.is_none_or(|to| presses[i] <= Fraction::from_integer(to))
+        };
+        basis.iter().all(|&i| within(i)).then_some(presses)
+    }
+}
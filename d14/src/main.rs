@@ -1,11 +1,13 @@
+mod tree;
+
 use itertools::Itertools;
 use lib2d::Point2d;
-use std::collections::HashSet;
-use std::{fs, io};
+use libparse::{ParseResult, Source};
 
 type CoordSize = i32;
 type Point = Point2d<CoordSize>;
 
+#[derive(Clone)]
 struct Bot {
     position: Point,
     velocity: Point,
@@ -16,111 +18,250 @@ impl Bot {
         Bot { position, velocity }
     }
 
-    fn tick(&mut self, grid_size: &Point) {
-        self.position = (self.position + self.velocity) % *grid_size;
+    /// Where the bot is after `seconds`, wrapping around the edges of the arena.
+    fn position_at(&self, seconds: i64, arena: Point) -> Point {
+        // each axis comes back around on its own, so there's never more than one lap of it to
+        // multiply out, and that fits in an i64
+        let axis = |position: CoordSize, velocity: CoordSize, length: CoordSize| {
+            let length = length as i64;
+            (position as i64 + velocity as i64 * seconds.rem_euclid(length)).rem_euclid(length) as CoordSize
+        };
+        Point2d::new(
+            axis(self.position.x, self.velocity.x, arena.x),
+            axis(self.position.y, self.velocity.y, arena.y),
+        )
     }
+}
 
-    fn get_quadrant(&self, grid_size: &Point) -> Option<usize> {
-        let half_grid_x = grid_size.x / 2;
-        let half_grid_y = grid_size.y / 2;
-        if self.position.x == half_grid_x || self.position.y == half_grid_y {
-            return None;
-        }
+struct Config {
+    // how wide and tall the arena is; the puzzle's is 101x103, and its example's 11x7
+    arena: Point,
+    // split the arena into a k x k grid for part 1
+    partition: CoordSize,
+    // print the partition and how big the picture's clump is to stderr
+    explain: bool,
+    // print the picture the bots make to stderr
+    show: bool,
+}
 
-        if self.position.y < half_grid_y {
-            if self.position.x < half_grid_x {
-                Some(1)
-            } else {
-                Some(2)
-            }
-        } else {
-            if self.position.x < half_grid_x {
-                Some(3)
-            } else {
-                Some(4)
+impl Config {
+    fn from_args(args: Vec<String>) -> Result<Config, String> {
+        let mut config = Config { arena: Point2d::new(101, 103), partition: 2, explain: false, show: false };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--arena" => {
+                    let spec = args.next().ok_or("--arena needs a width and height, like 11,7")?;
+                    config.arena = spec.split_once(',')
+                        .and_then(|(width, height)| Some(Point2d::new(width.parse().ok()?, height.parse().ok()?)))
+                        .filter(|arena: &Point| arena.x > 0 && arena.y > 0)
+                        .ok_or(format!("Expected an arena width and height, like 11,7, not {spec:?}"))?;
+                }
+                "--partition" => {
+                    let k = args.next().ok_or("--partition needs how many parts to split each side into")?;
+                    config.partition = k.parse()
+                        .ok()
+                        .filter(|&k| k > 0)
+                        .ok_or(format!("Expected how many parts to split each side into, not {k:?}"))?;
+                }
+                "--explain" => config.explain = true,
+                "--show" => config.show = true,
+                _ => return Err(format!(
+                    "Unrecognized argument {arg:?}; d14 takes --arena, --partition, --explain, and --show"
+                )),
             }
         }
+
+        Ok(config)
+    }
+
+    /// The arena, as long as every bot starts inside it. That depends on the bots, so it can only
+    /// be checked once they're loaded.
+    fn arena(&self, bots: &[Bot]) -> Result<Point, String> {
+        let arena = self.arena;
+        if let Some(bot) = bots.iter().find(|bot| bot.position % arena != bot.position) {
+            return Err(format!("a bot starts at {:?}, outside a {}x{} arena", bot.position, arena.x, arena.y));
+        }
+        if arena.x < self.partition || arena.y < self.partition {
+            return Err(format!("a {}x{} arena can't be split {} ways", arena.x, arena.y, self.partition));
+        }
+        Ok(arena)
     }
 }
 
 fn main() {
-    part1();
-    part2();
+    let config = Config::from_args(runner::day_args()).unwrap_or_else(|message| runner::bad_day_args("d14", &message));
+    runner::run("d14", load_bots, |bots| part1(bots, &config), |bots| part2(bots, &config));
 }
 
-fn part1() {
-    let mut bots = load_bots();
-    let grid_size = Point2d::new(101, 103);
-
-    let counts = bots.iter_mut()
-        .flat_map(|bot| {
-            for _ in 0..100 {
-                bot.tick(&grid_size);
-            }
-            let quad = bot.get_quadrant(&grid_size);
-            // println!("Bot in {:?} at {:?}", quad, bot.position);
-            quad
-        })
-        .counts_by(|quad| quad);
+/// Which of `k` equal bands along a side of `length` the tile at `coord` is in. Tiles that
+/// straddle two bands (like the middle one, when there's an odd number) aren't in either.
+fn band(coord: CoordSize, length: CoordSize, k: CoordSize) -> Option<usize> {
+    // measured in k-ths of a tile, each band is `length` long; that can go past an i32
+    let (coord, length, k) = (coord as i64, length as i64, k as i64);
+    let (first, last) = (coord * k / length, ((coord + 1) * k - 1) / length);
+    (first == last).then_some(first as usize)
+}
 
-    let score: usize = counts.values().product();
-    println!("Part 1: {score}");
+/// How many bots are in each part of the arena, split `k` ways along each side; rows go down
+/// the arena.
+fn partition(positions: impl Iterator<Item = Point>, arena: Point, k: CoordSize) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; k as usize]; k as usize];
+    for position in positions {
+        if let (Some(column), Some(row)) = (band(position.x, arena.x, k), band(position.y, arena.y, k)) {
+            counts[row][column] += 1;
+        }
+    }
+    counts
 }
 
-fn part2() {
-    // I don't know what a christmas tree looks like?
-    let mut bots = load_bots();
-    let grid_size = &Point2d::new(101, 103);
-    
-    let mut stdin = io::stdin();
-    let input = & mut String::new();
-    let mut i = 0;
-    
-    let mut seen_arrangements = HashSet::new();
-    loop {
-        i += 1;
-        let points: HashSet<Point> = bots.iter_mut()
-            .map(|bot| {
-                bot.tick(grid_size);
-                bot.position
-            })
-            .collect();
-        for y in 0..grid_size.y {
-            for x in 0..grid_size.x {
-                let char =  if points.contains(&Point2d::new(x, y)) {'*'} else {' '};
-                print!("{char}");
-            }
-            println!();
+fn part1(bots: &[Bot], config: &Config) -> Option<usize> {
+    let arena = match config.arena(bots) {
+        Ok(arena) => arena,
+        Err(message) => {
+            eprintln!("d14: {message}");
+            return None;
+        }
+    };
+    let counts = partition(bots.iter().map(|bot| bot.position_at(100, arena)), arena, config.partition);
+
+    if config.explain {
+        let width = counts.iter().flatten().max().map_or(1, |count| count.to_string().len());
+        for row in &counts {
+            eprintln!("{}", row.iter().map(|count| format!("{count:>width$}")).join(" "));
         }
-        println!("\n\n{i}");
-        
-        let pt_array = points.into_iter().sorted().collect_vec();
-        let duplicate = !seen_arrangements.insert(pt_array);
-        if duplicate {
-            println!("Duplicate!");
-            break;
+    }
+
+    Some(counts.iter().flatten().product())
+}
+
+fn part2(bots: &[Bot], config: &Config) -> Option<i64> {
+    let arena = match config.arena(bots) {
+        Ok(arena) => arena,
+        Err(message) => {
+            eprintln!("d14: {message}");
+            return None;
         }
-        stdin.read_line(input).unwrap();
+    };
+
+    let Some(sighting) = tree::find_tree(bots, arena) else {
+        eprintln!("The bots never bunch up on both axes at once");
+        return None;
+    };
+    if config.explain {
+        eprintln!("At {}s, {} bots are in one clump", sighting.second, sighting.largest_cluster());
     }
+    if config.show {
+        eprint!("{}", sighting.to_text());
+    }
+
+    Some(sighting.second)
 }
 
-fn load_bots() -> Vec<Bot> {
-    fs::read_to_string("d14/input").unwrap()
+fn load_bots(input: &str) -> ParseResult<Vec<Bot>> {
+    let source = Source::new(input);
+    input
         .lines()
         .map(|line| {
-            let mut parts = line.split(" ");
-            let pos = parts.next().unwrap().to_string();
-            let vel = parts.next().unwrap().to_string();
+            let [px, py, vx, vy] = source.integers_exact(line, "a position and velocity")?;
 
-            Bot::new(parse_point(&pos), parse_point(&vel))
+            Ok(Bot::new(Point2d::new(px, py), Point2d::new(vx, vy)))
         })
         .collect()
 }
 
-fn parse_point(pair_str: &str) -> Point {
-    let mut parts = pair_str[2..].split(",");
-    let x: CoordSize = parts.next().unwrap().parse().unwrap();
-    let y: CoordSize = parts.next().unwrap().parse().unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_negative_velocities() {
+        let bots = load_bots("p=0,4 v=3,-3\np=6,3 v=-1,-3\n").unwrap();
+        assert_eq!(bots[0].position, Point2d::new(0, 4));
+        assert_eq!(bots[0].velocity, Point2d::new(3, -3));
+        assert_eq!(bots[1].velocity, Point2d::new(-1, -3));
+    }
+
+    const EXAMPLE: &str = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\np=2,0 v=2,-1\np=0,0 v=1,3\np=3,0 v=-2,-2\n\
+                           p=7,6 v=-1,-3\np=3,0 v=-1,-2\np=9,3 v=2,3\np=7,3 v=-1,2\np=2,4 v=2,-3\np=9,5 v=-3,-3\n";
+
+    #[test]
+    fn example() {
+        let bots = load_bots(EXAMPLE).unwrap();
+        let config = Config::from_args(vec![String::from("--arena"), String::from("11,7")]).unwrap();
+        assert_eq!(config.arena(&bots), Ok(Point2d::new(11, 7)));
+        assert_eq!(part1(&bots, &config), Some(12));
+
+        let arena = Point2d::new(11, 7);
+        assert_eq!(bots[10].position_at(5, arena), Point2d::new(1, 3));
+        assert_eq!(bots[10].position_at(5 + 77 * 1000, arena), Point2d::new(1, 3));
+        assert_eq!(bots[10].position_at(-5, arena), Point2d::new(3, 5));
+        let positions = bots.iter().map(|bot| bot.position_at(100, arena));
+        assert_eq!(partition(positions, arena, 2), vec![vec![1, 3], vec![4, 1]]);
+
+        // the puzzle's own arena, unless it's told otherwise
+        let big = Config::from_args(vec![]).unwrap();
+        assert_eq!(big.arena(&bots), Ok(Point2d::new(101, 103)));
+
+        // the example's bots don't all fit in a smaller arena, and it can't be split more ways
+        // than it has tiles
+        let small = Config::from_args(vec![String::from("--arena"), String::from("5,5")]).unwrap();
+        assert!(small.arena(&bots).is_err());
+        assert_eq!(part1(&bots, &small), None);
+        let split = Config::from_args(["--arena", "11,7", "--partition", "8"].map(String::from).to_vec()).unwrap();
+        assert_eq!(split.arena(&bots), Err(String::from("a 11x7 arena can't be split 8 ways")));
+    }
+
+    #[test]
+    fn big_arenas() {
+        let bot = Bot::new(Point2d::new(0, 0), Point2d::new(1999, -1999));
+        let arena = Point2d::new(2000, 2001);
+        assert_eq!(bot.position_at(3_000_000, arena), Point2d::new(0, 1002));
+
+        let arena = |size: &str| Config::from_args(vec![String::from("--arena"), String::from(size)]).map(|config| config.arena);
+        assert_eq!(arena("2000,2001"), Ok(Point2d::new(2000, 2001)));
+        assert_eq!(arena("50000,50000"), Ok(Point2d::new(50000, 50000)));
+        assert!(arena("11x7").is_err());
+        assert!(arena("0,7").is_err());
+        // a whole lap of a side, split three ways, is bigger than an i32
+        assert_eq!(band(i32::MAX - 1, i32::MAX, 3), Some(2));
+    }
 
-    Point2d::new(x, y)
-}
\ No newline at end of file
+    #[test]
+    fn bands() {
+        // the middle tile of an odd side belongs to neither half
+        assert_eq!((0..11).map(|x| band(x, 11, 2)).collect::<Vec<_>>()[4..7], [Some(0), None, Some(1)]);
+        // an even split leaves nothing out
+        assert!((0..12).all(|x| band(x, 12, 3) == Some(x as usize / 4)));
+        assert_eq!((0..7).filter_map(|x| band(x, 7, 3)).collect::<Vec<_>>(), vec![0, 0, 1, 2, 2]);
+    }
+
+    #[test]
+    fn finds_the_picture() {
+        let arena = Point2d::new(11, 7);
+        let second = 30;
+        // a little block of bots, all arriving at once from every direction
+        let bots: Vec<Bot> = (0..30)
+            .map(|idx| {
+                let velocity = Point2d::new(idx % 10 + 1, idx % 6 + 1);
+                let target = Point2d::new(4 + idx % 3, 2 + idx / 3 % 3);
+                Bot::new((target - velocity * second) % arena, velocity)
+            })
+            .collect();
+
+        let sighting = tree::find_tree(&bots, arena).unwrap();
+        assert_eq!(sighting.second, 30);
+        assert_eq!(sighting.largest_cluster(), 9);
+        assert_eq!(sighting.to_text().lines().nth(3), Some("    ***    "));
+    }
+
+    #[test]
+    fn broken_bot() {
+        let input = "p=0,4 v=3,-3\np=6,3 v=-1\n";
+        let err = load_bots(input).err().unwrap();
+        assert_eq!((err.line, err.column), (2, 11));
+        assert_eq!(err.expected, "4 of a position and velocity");
+        assert_eq!(err.found, None);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Bot, Point};
+use lib2d::{label_components, Connectivity, Grid};
+
+/// The second the bots line up into a picture, and what it looks like.
+pub struct Sighting {
+    pub second: i64,
+    pub frame: Grid<bool>,
+}
+
+impl Sighting {
+    /// The most bots touching each other in one clump, which is big when there's a picture.
+    pub fn largest_cluster(&self) -> usize {
+        let labels = label_components(&self.frame, Connectivity::Four);
+        labels.components.iter().filter(|component| component.value).map(|component| component.area).max().unwrap_or(0)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (point, &occupied) in self.frame.iter() {
+            text.push(if occupied { '*' } else { ' ' });
+            if point.x as usize == self.frame.width() - 1 {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/// How spread out some coordinates are: n² times their variance, which keeps it in whole
+/// numbers. Squaring them can go past an i64 in a big enough arena.
+fn spread(coords: impl Iterator<Item = i128>) -> i128 {
+    let (count, sum, squares) = coords.fold((0, 0, 0), |(count, sum, squares), coord| (count + 1, sum + coord, squares + coord * coord));
+    count * squares - sum * sum
+}
+
+/// Bots only ever move in straight lines, so their x's come back around every `width` seconds
+/// and their y's every `height`, each on its own. A picture needs them bunched up on both
+/// axes: find the second each axis is tightest within its own period, and then the second both
+/// of those happen at once.
+pub fn find_tree(bots: &[Bot], arena: Point) -> Option<Sighting> {
+    let tightest = |period: i32, axis: fn(Point) -> i32| {
+        (0..period as i64)
+            .min_by_key(|&second| spread(bots.iter().map(|bot| axis(bot.position_at(second, arena)) as i128)))
+            .unwrap()
+    };
+    let (best_x, best_y) = (tightest(arena.x, |point| point.x), tightest(arena.y, |point| point.y));
+
+    let (second, _) = crt((best_x, arena.x as i64), (best_y, arena.y as i64))?;
+
+    let mut frame = Grid::new(arena.x as usize, arena.y as usize, false);
+    for bot in bots {
+        frame[bot.position_at(second, arena)] = true;
+    }
+
+    Some(Sighting { second, frame })
+}
+
+/// The `t` in `0..lcm(m, n)` with `t = a (mod m)` and `t = b (mod n)`, and the lcm. The moduli
+/// don't have to be coprime, but then there might not be any such `t`.
+fn crt((a, m): (i64, i64), (b, n): (i64, i64)) -> Option<(i64, i64)> {
+    let (gcd, inverse, _) = extended_gcd(m, n);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+
+    let lcm = m / gcd * n;
+    // a + m * k, where m * k = b - a (mod n)
+    let k = ((b - a) / gcd * inverse).rem_euclid(n / gcd);
+    Some(((a + m * k).rem_euclid(lcm), lcm))
+}
+
+/// `(g, x, y)` with `a * x + b * y = g`.
+fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
@@ -1,9 +1,9 @@
 use itertools::Itertools;
 use lib2d::Direction::{Down, Left, Right, Up};
 use lib2d::{dir_delta, Direction, Point2d};
+use libparse::{ParseResult, Source};
 use std::cell::RefCell;
 use std::collections::HashSet;
-use std::fs;
 use std::hash::{Hash, Hasher};
 
 type Coord = i32;
@@ -65,6 +65,8 @@ impl Board {
 
         let instr = self.instructions.pop().unwrap();
         let bot = &self.bot;
+        // crates only hash by id, so moving them around inside the set is fine
+        #[allow(clippy::mutable_key_type)]
         let mut impacted_boxes = HashSet::new();
         if self.could_move(bot, &instr, &mut impacted_boxes) {
             impacted_boxes.iter().for_each(|b| {
@@ -74,6 +76,7 @@ impl Board {
         true
     }
 
+    #[allow(clippy::mutable_key_type)]
     fn could_move<'a>(&'a self, mover: &'a SlidyCrate, instr: &Direction,
                   impacted_boxes: &mut HashSet<&'a SlidyCrate>) -> bool {
         impacted_boxes.insert(mover);
@@ -100,87 +103,59 @@ impl Board {
     }
 }
 
+#[derive(Debug)]
+struct Layout {
+    walls: Vec<Point>,
+    boxes: Vec<Point>,
+    bot: Point,
+    instructions: Vec<Direction>,
+}
+
 fn main() {
-    part1();
-    part2();
+    runner::run("d15", load_layout, part1, part2);
 }
 
-fn part1() {
-    let mut board = load_board(1);
+fn part1(layout: &Layout) -> Coord {
+    let mut board = load_board(layout, 1);
     loop {
         if !board.tick() {
             break;
         }
     }
 
-    let sum: Coord = board.boxes.iter().map(|b| b.gps()).sum();
-
-    println!("Part 1: {sum}");
+    board.boxes.iter().map(|b| b.gps()).sum()
 }
 
-fn part2() {
-    let mut board = load_board(2);
+fn part2(layout: &Layout) -> Coord {
+    let mut board = load_board(layout, 2);
     loop {
         if !board.tick() {
             break;
         }
     }
 
-    let sum: Coord = board.boxes.iter().map(|b| b.gps()).sum();
-
-    println!("Part 2: {sum}");
+    board.boxes.iter().map(|b| b.gps()).sum()
 }
 
-fn load_board(width: Coord) -> Board {
+fn load_board(layout: &Layout, width: Coord) -> Board {
+    // everything except the bot gets stretched sideways
+    let stretch = |point: &Point| Point2d::new(width * point.x, point.y);
+
     let mut walls = HashSet::new();
-    let mut boxes = Vec::new();
-    let mut bot: Option<Point> = None;
+    for wall in layout.walls.iter() {
+        for i in 0..width {
+            let offset_point = Point2d::new(i, 0) + stretch(wall);
+            walls.insert(offset_point);
+        }
+    }
 
-    let (map_str, instruction_str) = fs::read_to_string("d15/input")
-        .unwrap()
-        .split("\n\n")
-        .map(|p| p.to_string())
-        .collect_tuple()
-        .unwrap();
-    let mut crate_id = 1;
-    map_str.lines().enumerate().for_each(|(y, line)| {
-        line.chars().enumerate().for_each(|(x, char)| {
-            let point = Point2d::new(
-                width * x as Coord,
-                y as Coord
-            );
-            match char {
-                '#' => {
-                    for i in 0..width {
-                        let offset_point = Point2d::new(i, 0) + point;
-                        walls.insert(offset_point);
-                    }
-                }
-                'O' => {
-                    boxes.push(SlidyCrate {
-                        id: crate_id,
-                        loc: RefCell::new(point),
-                        width
-                    });
-                    crate_id += 1;
-                },
-                '@' => bot = Some(point),
-                _ => {}
-            }
-        })
-    });
-
-    let moves = instruction_str
-        .chars()
-        .flat_map(|dir| match dir {
-            '^' => Some(Up),
-            'v' => Some(Down),
-            '>' => Some(Right),
-            '<' => Some(Left),
-            _ => None,
+    let boxes = layout.boxes.iter()
+        .enumerate()
+        .map(|(idx, point)| SlidyCrate {
+            id: idx + 1,
+            loc: RefCell::new(stretch(point)),
+            width
         })
-        // reverse so we can pop
-        .rev()
         .collect();
 
     Board {
@@ -188,9 +163,51 @@ fn load_board(width: Coord) -> Board {
         boxes,
         bot: SlidyCrate {
             id: 0,
-            loc: RefCell::new(bot.unwrap()),
+            loc: RefCell::new(stretch(&layout.bot)),
             width: 1
         },
-        instructions: moves,
+        instructions: layout.instructions.clone(),
     }
 }
+
+fn load_layout(input: &str) -> ParseResult<Layout> {
+    let source = Source::new(input);
+    let mut walls = Vec::new();
+    let mut boxes = Vec::new();
+    let mut bot: Option<Point> = None;
+
+    let [map_str, instruction_str] = source.sections_exact(input)?;
+    for (y, line) in map_str.lines().enumerate() {
+        for (x, (idx, char)) in line.char_indices().enumerate() {
+            let point = Point2d::new(x as Coord, y as Coord);
+            match char {
+                '#' => walls.push(point),
+                'O' => boxes.push(point),
+                '@' => bot = Some(point),
+                '.' => {}
+                _ => return Err(source.error(&line[idx..idx + char.len_utf8()], "one of '#', 'O', '@', or '.'")),
+            }
+        }
+    }
+
+    let mut moves = Vec::new();
+    for (idx, dir) in instruction_str.char_indices() {
+        match dir {
+            '^' => moves.push(Up),
+            'v' => moves.push(Down),
+            '>' => moves.push(Right),
+            '<' => moves.push(Left),
+            '\n' => {}
+            _ => return Err(source.error(&instruction_str[idx..idx + dir.len_utf8()], "a move (^, v, <, or >)")),
+        }
+    }
+    // reverse so we can pop
+    moves.reverse();
+
+    Ok(Layout {
+        walls,
+        boxes,
+        bot: bot.ok_or_else(|| source.missing(map_str, "a robot ('@')"))?,
+        instructions: moves,
+    })
+}
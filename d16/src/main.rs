@@ -1,7 +1,7 @@
 use lib2d::Direction::{Down, Left, Right, Up};
 use lib2d::{dir_delta, Direction, Point2d};
+use libparse::{ParseResult, Source};
 use std::collections::HashSet;
-use std::fs;
 
 type Msize = i32;
 type Point = Point2d<Msize>;
@@ -27,7 +27,7 @@ impl Maze {
 
         while !unexplored.is_empty() {
             // find the easiest-to-get-to unexplored tile
-            unexplored.sort_by_key(|pose| -1 * pose.score as i64);
+            unexplored.sort_by_key(|pose| -(pose.score as i64));
             let cur_tile = unexplored.pop().unwrap();
 
             // make sure we haven't already explored it
@@ -75,7 +75,7 @@ impl Maze {
 
         while !unexplored.is_empty() {
             // find the easiest-to-get-to unexplored tile
-            unexplored.sort_by_key(|pose| -1 * pose.score as i64);
+            unexplored.sort_by_key(|pose| -(pose.score as i64));
             let mut cur_tile = unexplored.pop().unwrap();
             
             // split out all tiles that have the same pose as this tile
@@ -198,57 +198,57 @@ impl ExploredTile {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d16", load_maze, part1, part2);
 }
 
-fn part1() {
-    let maze = load_maze();
+fn part1(maze: &Maze) -> usize {
     let exit_tile = maze.solve();
-    println!("Part 1: {:?}", exit_tile.score);
+    exit_tile.score
 }
 
 
 
-fn part2() {
-    let maze = load_maze();
+fn part2(maze: &Maze) -> usize {
     let exit_tiles = maze.solve_all();
     let good_seats: HashSet<_> = exit_tiles.into_iter()
         .flat_map(|tile| tile.route.into_iter())
         .collect();
 
-    println!("Part 2: {:?}", good_seats.len());
+    good_seats.len()
 }
 
 
 
-fn load_maze() -> Maze {
+fn load_maze(input: &str) -> ParseResult<Maze> {
+    let source = Source::new(input);
     let mut points: HashSet<Point> = HashSet::new();
     let mut start = None;
     let mut goal = None;
 
-    fs::read_to_string("d16/input").unwrap()
-        .lines().enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate()
-                .for_each(|(x, tile)| {
-                    let point = Point2d::new(x as Msize, y as Msize);
-                    match tile {
-                        '.' => {
-                            points.insert(point);
-                        },
-                        'S' => {
-                            points.insert(point);
-                            start = Some(point);
-                        },
-                        'E' => {
-                            points.insert(point);
-                            goal = Some(point);
-                        }
-                        _ => {}
-                    }
-                })
-        });
+    for (y, line) in input.lines().enumerate() {
+        for (x, (idx, tile)) in line.char_indices().enumerate() {
+            let point = Point2d::new(x as Msize, y as Msize);
+            match tile {
+                '.' => {
+                    points.insert(point);
+                },
+                'S' => {
+                    points.insert(point);
+                    start = Some(point);
+                },
+                'E' => {
+                    points.insert(point);
+                    goal = Some(point);
+                }
+                '#' => {}
+                _ => return Err(source.error(&line[idx..idx + tile.len_utf8()], "one of '.', '#', 'S', or 'E'")),
+            }
+        }
+    }
 
-    Maze { points, start: start.unwrap(), goal: goal.unwrap() }
+    Ok(Maze {
+        points,
+        start: start.ok_or_else(|| source.missing(input, "a start ('S')"))?,
+        goal: goal.ok_or_else(|| source.missing(input, "an end ('E')"))?,
+    })
 }
@@ -1,5 +1,5 @@
 use itertools::Itertools;
-use std::fs;
+use libparse::{ParseResult, Source};
 
 type RegSize = usize;
 
@@ -27,11 +27,11 @@ impl MicroProcessor {
 
     fn run(&mut self, debug: bool) {
         if debug {
-            println!("{:?}", self);
+            eprintln!("{:?}", self);
         }
         while self.tick(debug) {
             if debug {
-                println!("{:?}", self);
+                eprintln!("{:?}", self);
             }
         }
     }
@@ -50,7 +50,7 @@ impl MicroProcessor {
 
         if debug {
             let opcode_name = Self::opcode_name(opcode);
-            println!("Got {opcode_name}[{opcode}] L: {literal_operand} C:{combo_operand}");
+            eprintln!("Got {opcode_name}[{opcode}] L: {literal_operand} C:{combo_operand}");
         }
 
         match opcode {
@@ -87,7 +87,7 @@ impl MicroProcessor {
     }
 
     fn bxl(&mut self, operand: RegSize) {
-        self.reg_b = self.reg_b ^ operand;
+        self.reg_b ^= operand;
     }
 
     fn bst(&mut self, operand: RegSize) {
@@ -101,7 +101,7 @@ impl MicroProcessor {
     }
 
     fn bxc(&mut self, _operand: RegSize) {
-        self.reg_b = self.reg_b ^ self.reg_c;
+        self.reg_b ^= self.reg_c;
     }
 
     fn out(&mut self, operand: RegSize) {
@@ -136,19 +136,16 @@ impl MicroProcessor {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d17", load_machine, part1, part2);
 }
 
-fn part1() {
-    let mut compy = load_machine();
+fn part1(compy: &MicroProcessor) -> String {
+    let mut compy = compy.clone();
     compy.run(false);
-    let output = compy.output.iter().join(",");
-    println!("Part 1: {output}");
+    compy.output.iter().join(",")
 }
 
-fn part2() {
-    let base_compy = load_machine();
+fn part2(base_compy: &MicroProcessor) -> RegSize {
 
     let mut candidate_values = vec![0];
     // the last output only cares about a % 8
@@ -181,28 +178,39 @@ fn part2() {
         candidate_values = new_candidates;
     }
 
-    let min_a = candidate_values.iter().min().unwrap();
-    println!("Part 2: {min_a}");
+    *candidate_values.iter().min().unwrap()
 }
 
-fn load_machine() -> MicroProcessor {
-    let text = fs::read_to_string("d17/input").unwrap();
-    let (reg_a, reg_b, reg_c, _, program) = text
-        .lines()
-        .collect_tuple()
-        .unwrap();
-
-    let (_, reg_a) = reg_a.split(": ").collect_tuple().unwrap();
-    let (_, reg_b) = reg_b.split(": ").collect_tuple().unwrap();
-    let (_, reg_c) = reg_c.split(": ").collect_tuple().unwrap();
-    let (_, program) = program.split(": ").collect_tuple().unwrap();
-
-    let reg_a = reg_a.parse().unwrap();
-    let reg_b = reg_b.parse().unwrap();
-    let reg_c = reg_c.parse().unwrap();
-    let program = program.split(",")
-        .map(|n| n.parse().unwrap())
-        .collect();
-
-    MicroProcessor::new(reg_a, reg_b, reg_c, program)
+fn load_machine(text: &str) -> ParseResult<MicroProcessor> {
+    let source = Source::new(text);
+    let [reg_a, reg_b, reg_c, _, program] =
+        source.take(text, text.lines(), "3 registers, a blank line, and a program")?;
+
+    let reg_a = source.parse(source.field(reg_a, "Register A")?, "a register value")?;
+    let reg_b = source.parse(source.field(reg_b, "Register B")?, "a register value")?;
+    let reg_c = source.parse(source.field(reg_c, "Register C")?, "a register value")?;
+    let program = source.list(source.field(program, "Program")?, "a 3-bit number")?;
+
+    Ok(MicroProcessor::new(reg_a, reg_b, reg_c, program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_register() {
+        let text = "Register A: 729\nRegister B: -1\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        let err = load_machine(text).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 13));
+        assert_eq!(err.to_string(), r#"2:13: expected a register value, found "-1""#);
+    }
+
+    #[test]
+    fn missing_program() {
+        let text = "Register A: 729\nRegister B: 0\nRegister C: 0\n";
+        let err = load_machine(text).unwrap_err();
+        assert_eq!(err.expected, "3 registers, a blank line, and a program");
+        assert_eq!(err.found, None);
+    }
 }
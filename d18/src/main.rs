@@ -1,8 +1,7 @@
-use itertools::Itertools;
 use lib2d::Direction::{Down, Left, Right, Up};
 use lib2d::{dir_delta, Point2d};
+use libparse::{ParseResult, Source};
 use std::collections::HashSet;
-use std::fs;
 
 type Bsize = i32;
 type Point = Point2d<Bsize>;
@@ -14,11 +13,10 @@ struct ExploredTile {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d18", load_bytes, |bytes| part1(bytes), |bytes| part2(bytes));
 }
 
-fn part1() {
+fn part1(bytes: &[Point]) -> Option<usize> {
     let arena_size = 71;
     let bad_point_len = 1024;
 
@@ -30,20 +28,17 @@ fn part1() {
     }
     let goal = Point2d::new(arena_size - 1, arena_size - 1);
 
-    load_bytes()
+    bytes
         .iter()
         .take(bad_point_len)
         .for_each(|point| {
             maze.remove(point);
         });
-    if let Some(score) = run_maze(goal, &maze) {
-        println!("Part 1: {}", score.route.len());
-    } else {
-        println!("I got lost");
-    }
+    // no route means I got lost
+    run_maze(goal, &maze).map(|score| score.route.len())
 }
 
-fn part2() {
+fn part2(bytes: &[Point]) -> Option<String> {
     let arena_size = 71;
 
     let mut maze = HashSet::new();
@@ -55,7 +50,7 @@ fn part2() {
     let goal = Point2d::new(arena_size - 1, arena_size - 1);
 
     let mut prior_route: Option<HashSet<Point>> = None;
-    for bad_byte in load_bytes() {
+    for &bad_byte in bytes {
         maze.remove(&bad_byte);
         // if there's a prior route
         if let Some(ref prior_route) = prior_route {
@@ -68,13 +63,15 @@ fn part2() {
 
         if let Some(tile) = run_maze(goal, &maze) {
             // update the route to be the points used to solve this maze
-            prior_route = Some(HashSet::from_iter(tile.route.into_iter()));
+            prior_route = Some(HashSet::from_iter(tile.route));
         } else {
             // there's no route out, we've solved it.
-            println!("Part 2: {},{}", bad_byte.x, bad_byte.y);
-            break;
+            return Some(format!("{},{}", bad_byte.x, bad_byte.y));
         }
     }
+
+    eprintln!("The exit never got blocked");
+    None
 }
 
 fn run_maze(goal: Point, maze: &HashSet<Point>) -> Option<ExploredTile> {
@@ -84,7 +81,7 @@ fn run_maze(goal: Point, maze: &HashSet<Point>) -> Option<ExploredTile> {
         route: vec![],
     }];
     while !unexplored.is_empty() {
-        unexplored.sort_by_key(|et| -1 * et.route.len() as i32);
+        unexplored.sort_by_key(|et| -(et.route.len() as i32));
         let cur_tile = unexplored.pop().unwrap();
 
         // we've already been here
@@ -115,16 +112,10 @@ fn run_maze(goal: Point, maze: &HashSet<Point>) -> Option<ExploredTile> {
     None
 }
 
-fn load_bytes() -> Vec<Point> {
-    fs::read_to_string("d18/input").unwrap()
+fn load_bytes(input: &str) -> ParseResult<Vec<Point>> {
+    let source = Source::new(input);
+    input
         .lines()
-        .map(|line| {
-            let (x, y) = line.split(",")
-                .map(|n| n.parse().unwrap())
-                .collect_tuple()
-                .unwrap();
-            Point2d::new(x, y)
-        })
-        .collect_vec()
-
+        .map(|line| source.point(line, "a coordinate"))
+        .collect()
 }
\ No newline at end of file
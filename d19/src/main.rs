@@ -1,31 +1,25 @@
 use itertools::Itertools;
+use libparse::{ParseResult, Source};
 use std::collections::HashMap;
-use std::fs;
+
+type Towels = (Vec<String>, Vec<String>);
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d19", load_towels, part1, part2);
 }
 
-fn part1() {
-    let (towels, patterns) = load_towels();
-    let possible = patterns
+fn part1((towels, patterns): &Towels) -> usize {
+    patterns
         .iter()
-        .filter(|pattern| can_be_made_with_towels(pattern, &towels))
-        .count();
-
-    println!("Part 1: {possible}");
+        .filter(|pattern| can_be_made_with_towels(pattern, towels))
+        .count()
 }
 
-fn part2() {
-    let (towels, patterns) = load_towels();
-
+fn part2((towels, patterns): &Towels) -> usize {
     let mut memo = HashMap::new();
-    let sum: usize = patterns.iter()
-        .map(|pattern| count_ways_that_can_be_made(pattern, &towels, &mut memo))
-        .sum();
-
-    println!("Part 2: {sum}");
+    patterns.iter()
+        .map(|pattern| count_ways_that_can_be_made(pattern, towels, &mut memo))
+        .sum()
 }
 
 fn can_be_made_with_towels(pattern: &str, towels: &Vec<String>) -> bool {
@@ -63,14 +57,13 @@ fn count_ways_that_can_be_made(pattern: &str,
     count
 }
 
-fn load_towels() -> (Vec<String>, Vec<String>) {
-    let file = fs::read_to_string("d19/input").unwrap();
-
-    let (towels, patterns) = file.split("\n\n").collect_tuple().unwrap();
+fn load_towels(input: &str) -> ParseResult<Towels> {
+    let source = Source::new(input);
+    let [towels, patterns] = source.sections_exact(input)?;
 
     let towels = towels.split(", ").map(|s| s.to_string()).collect_vec();
 
     let patterns = patterns.lines().map(|s| s.to_string()).collect_vec();
 
-    (towels, patterns)
+    Ok((towels, patterns))
 }
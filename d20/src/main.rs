@@ -1,8 +1,8 @@
 use itertools::Itertools;
 use lib2d::Direction::{Down, Left, Right, Up};
 use lib2d::{dir_delta, Direction, Point2d};
+use libparse::{ParseResult, Source};
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 type Msize = i32;
 type Point = Point2d<Msize>;
@@ -14,15 +14,12 @@ struct Maze {
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d20", load_maze, part1, part2);
 }
 
-fn part1() {
-    let maze = load_maze();
-
+fn part1(maze: &Maze) -> usize {
     // build up a distance of each existing point in the maze from the end
-    let tile_scores = get_non_cheaty_scores(&maze);
+    let tile_scores = get_non_cheaty_scores(maze);
 
     let mut cheat_savings: HashMap<(Point, Direction), i32> = HashMap::new();
     for tile in maze.tiles.iter() {
@@ -41,10 +38,9 @@ fn part1() {
         }
     }
 
-    let at_least_100 = cheat_savings.values()
+    cheat_savings.values()
         .filter(|value| **value >= 100)
-        .count();
-    println!("Part 1: {at_least_100}");
+        .count()
 }
 
 fn get_non_cheaty_scores(maze: &Maze) -> HashMap<Point, i32> {
@@ -71,9 +67,7 @@ fn get_non_cheaty_scores(maze: &Maze) -> HashMap<Point, i32> {
     maze_map
 }
 
-fn part2() {
-    let maze = load_maze();
-
+fn part2(maze: &Maze) -> usize {
     // build up a map of all the places you can go within 20 seconds
     let mut cheaty_deltas = get_cheaty_deltas(20);
 
@@ -86,7 +80,7 @@ fn part2() {
     }
 
     // build up a distance of each existing point in the maze from the end
-    let tile_scores = get_non_cheaty_scores(&maze);
+    let tile_scores = get_non_cheaty_scores(maze);
 
     let mut good_cheats = 0;
     for tile in maze.tiles.iter() {
@@ -104,7 +98,7 @@ fn part2() {
         }
     }
 
-    println!("Part 2: {good_cheats}");
+    good_cheats
 }
 
 fn get_cheaty_deltas(cheat_seconds: i32) -> HashMap<Point, i32> {
@@ -133,37 +127,36 @@ fn get_cheaty_deltas(cheat_seconds: i32) -> HashMap<Point, i32> {
     delta_map
 }
 
-fn load_maze() -> Maze {
+fn load_maze(input: &str) -> ParseResult<Maze> {
+    let source = Source::new(input);
     let mut start = None;
     let mut goal = None;
     let mut tiles = HashSet::new();
 
-    fs::read_to_string("d20/input").unwrap()
-        .lines()
-        .enumerate()
-        .for_each(|(y, line)| {
-            line.chars().enumerate().for_each(|(x, val)| {
-                let point = Point2d::new(x as Msize, y as Msize);
-                match val {
-                    'S' => {
-                        tiles.insert(point);
-                        start = Some(point);
-                    },
-                    'E' => {
-                        tiles.insert(point);
-                        goal = Some(point);
-                    },
-                    '.' => {
-                        tiles.insert(point);
-                    },
-                    _ => {}
-                }
-            });
-        });
+    for (y, line) in input.lines().enumerate() {
+        for (x, (idx, val)) in line.char_indices().enumerate() {
+            let point = Point2d::new(x as Msize, y as Msize);
+            match val {
+                'S' => {
+                    tiles.insert(point);
+                    start = Some(point);
+                },
+                'E' => {
+                    tiles.insert(point);
+                    goal = Some(point);
+                },
+                '.' => {
+                    tiles.insert(point);
+                },
+                '#' => {}
+                _ => return Err(source.error(&line[idx..idx + val.len_utf8()], "one of '.', '#', 'S', or 'E'")),
+            }
+        }
+    }
 
-    Maze {
-        start: start.unwrap(),
-        goal: goal.unwrap(),
+    Ok(Maze {
+        start: start.ok_or_else(|| source.missing(input, "a start ('S')"))?,
+        goal: goal.ok_or_else(|| source.missing(input, "an end ('E')"))?,
         tiles
-    }
+    })
 }
\ No newline at end of file
@@ -1,51 +1,37 @@
 use itertools::Itertools;
 use lib2d::Direction::{Down, Left, Right, Up};
 use lib2d::{dir_delta, Point2d};
-use std::fs;
+use libparse::{ParseResult, Source};
+use runner::Unsolved;
 
 type Point = Point2d<i32>;
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d21", load_codes, |codes| part1(codes), |codes| part2(codes));
 }
 
-fn part1() {
-    let codes = load_codes("d21/example1");
-    let tenkey_paths = convert_codes(&codes, get_tenkey_path);
-
-    print_paths(&codes, &tenkey_paths);
-
+// still a work in progress: this gets 127900 on the example, not 126384, so there's no
+// answer on record for it yet
+fn part1(codes: &[String]) -> usize {
+    let tenkey_paths = convert_codes(codes, get_tenkey_path);
     let rad_paths = convert_codes(&tenkey_paths, get_cross_path);
-    print_paths(&codes, &rad_paths);
-
     let frozen_paths = convert_codes(&rad_paths, get_cross_path);
-    print_paths(&codes, &frozen_paths);
 
     // let lobby_paths = convert_codes(&frozen_paths, get_cross_path);
-    // print_paths(&codes, &lobby_paths);
 
-    let score: usize = codes.iter().zip(frozen_paths.iter())
+    codes.iter().zip(frozen_paths.iter())
         .map(|(code, path)| {
             let code_int: usize = code[..3].parse().unwrap();
             code_int * path.len()
         })
-        .sum();
-
-    println!("Part 1: {score}");
+        .sum()
 }
 
-fn part2() {}
-
-fn print_paths(codes: &Vec<String>, paths: &Vec<String>) {
-
-    for (code, path) in codes.iter().zip(paths.iter()) {
-        println!("{}: {} ({})", code, path, path.len());
-    }
-    println!();
+fn part2(_codes: &[String]) -> Unsolved {
+    Unsolved
 }
 
-fn convert_codes(codes: &Vec<String>, get_path_fn: impl Fn(char, char) -> String) -> Vec<String> {
+fn convert_codes(codes: &[String], get_path_fn: impl Fn(char, char) -> String) -> Vec<String> {
     codes.iter()
         .map(|code| {
             let mut full_code = String::from("A");
@@ -59,6 +45,7 @@ fn convert_codes(codes: &Vec<String>, get_path_fn: impl Fn(char, char) -> String
         .collect()
 }
 
+#[allow(dead_code)]
 fn cross_instr(button: char) -> String {
     match button {
         'A' => String::from("A"),
@@ -150,9 +137,19 @@ fn get_tenkey_path(start: char, end: char) -> String {
     path
 }
 
-fn load_codes(path: &str) -> Vec<String> {
-    fs::read_to_string(path).unwrap()
+fn load_codes(input: &str) -> ParseResult<Vec<String>> {
+    let source = Source::new(input);
+    input
         .lines()
-        .map(|line| line.to_string())
+        .map(|line| {
+            // three digits, then 'A'
+            let well_formed = line.len() == 4
+                && line[..3].chars().all(|c| c.is_ascii_digit())
+                && line.ends_with('A');
+            if !well_formed {
+                return Err(source.error(line, "a door code like \"029A\""));
+            }
+            Ok(line.to_string())
+        })
         .collect()
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use itertools::Itertools;
+use libparse::{ParseResult, Source};
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 type Change4 = (i64, i64, i64, i64);
 struct MonkeySecret {
@@ -10,7 +10,7 @@ struct MonkeySecret {
     change_history: Vec<i64>
 }
 
-static debug: bool = false;
+static DEBUG: bool = false;
 
 impl MonkeySecret {
     fn new(initial_secret: i64) -> MonkeySecret {
@@ -51,53 +51,49 @@ impl MonkeySecret {
         }
 
         self.change_history.push(delta_price);
-        if debug {
-            println!("{}: {} ({})", self.secret, final_price, delta_price);
+        if DEBUG {
+            eprintln!("{}: {} ({})", self.secret, final_price, delta_price);
         }
         if self.change_history.len() == 4 {
             let delta4: Change4 = self.change_history
                 .iter()
-                .map(|i| *i)
+                .copied()
                 .collect_tuple().unwrap();
-            if debug {
-                println!("{:?}", delta4);
-            }
-            if !self.change_map.contains_key(&delta4) {
-                self.change_map.insert(delta4, final_price);
+            if DEBUG {
+                eprintln!("{:?}", delta4);
             }
+            self.change_map.entry(delta4).or_insert(final_price);
         }
 
         self.gen += 1;
     }
 
     fn mix(&mut self, val:i64) {
-        self.secret = self.secret ^ val;
+        self.secret ^= val;
     }
 
     fn prune(&mut self) {
-        self.secret = self.secret % 16777216;
+        self.secret %= 16777216;
     }
 }
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d22", load_secrets, |secrets| part1(secrets), |secrets| part2(secrets));
 }
 
-fn part1() {
-    let mut monkeys = load_monkeys();
+fn part1(secrets: &[i64]) -> i64 {
+    let mut monkeys = load_monkeys(secrets);
 
-    let sum: i64 = monkeys.iter_mut()
+    monkeys.iter_mut()
         .map(|monkey| {
             monkey.run(2000);
             monkey.secret
         })
-        .sum();
-    println!("Part 1: {sum}");
+        .sum()
 }
 
-fn part2() {
-    let mut monkeys = load_monkeys();
+fn part2(secrets: &[i64]) -> (Change4, i64) {
+    let mut monkeys = load_monkeys(secrets);
     for monkey in monkeys.iter_mut() {
         monkey.run(2000);
     }
@@ -107,7 +103,7 @@ fn part2() {
         candidates.extend(monkey.change_map.keys())
     }
 
-    println!("Looking at {} candidates", candidates.len());
+    eprintln!("Looking at {} candidates", candidates.len());
     let candidate_map: HashMap<Change4, i64> = candidates.iter()
         .map(|candidate| {
             let score: i64 = monkeys.iter()
@@ -121,14 +117,19 @@ fn part2() {
         .max_by_key(|(_change, score)| **score)
         .unwrap();
 
-    println!("Part 2: {:?} - {}", changes, score);
+    (*changes, *score)
+}
+
+fn load_monkeys(secrets: &[i64]) -> Vec<MonkeySecret> {
+    secrets.iter()
+        .map(|val| MonkeySecret::new(*val))
+        .collect()
 }
 
-fn load_monkeys() -> Vec<MonkeySecret> {
-    fs::read_to_string("d22/input")
-        .unwrap()
+fn load_secrets(input: &str) -> ParseResult<Vec<i64>> {
+    let source = Source::new(input);
+    input
         .lines()
-        .map(|line| line.parse().unwrap())
-        .map(|val| MonkeySecret::new(val))
+        .map(|line| source.parse(line, "a secret number"))
         .collect()
 }
\ No newline at end of file
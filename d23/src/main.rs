@@ -1,15 +1,13 @@
 use itertools::Itertools;
+use libparse::{ParseResult, Source};
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 fn main() {
-    part1();
-    part2();
+    runner::run("d23", load_pairs, |pairs| part1(pairs), |pairs| part2(pairs));
 }
 
-fn part1() {
-    let pair_list = load_pairs();
-    let direct_neighbors = get_neighbors(&pair_list);
+fn part1(pair_list: &[(String, String)]) -> usize {
+    let direct_neighbors = get_neighbors(pair_list);
 
     let mut thruples = HashSet::new();
     for (node, neighbors) in direct_neighbors.iter() {
@@ -33,22 +31,21 @@ fn part1() {
         }
     }
 
-    let admin_counts = thruples
+    thruples
         .iter()
         .filter(|(a, b, c)| a.starts_with('t') || b.starts_with('t') || c.starts_with('t'))
-        .count();
-    println!("Part 1: {admin_counts}");
+        .count()
 }
 
-fn get_neighbors(pairs: &Vec<(String, String)>) -> HashMap<String, Vec<String>> {
-    let mut direct_neighbors = HashMap::new();
+fn get_neighbors(pairs: &[(String, String)]) -> HashMap<String, Vec<String>> {
+    let mut direct_neighbors: HashMap<String, Vec<String>> = HashMap::new();
 
     for (a, b) in pairs.iter() {
-        let mut vec = direct_neighbors.remove(a).unwrap_or_else(|| vec![]);
+        let mut vec = direct_neighbors.remove(a).unwrap_or_default();
         vec.push(b.clone());
         direct_neighbors.insert(a.clone(), vec);
 
-        let mut vec = direct_neighbors.remove(b).unwrap_or_else(|| vec![]);
+        let mut vec = direct_neighbors.remove(b).unwrap_or_default();
         vec.push(a.clone());
         direct_neighbors.insert(b.clone(), vec);
     }
@@ -56,9 +53,8 @@ fn get_neighbors(pairs: &Vec<(String, String)>) -> HashMap<String, Vec<String>>
     direct_neighbors
 }
 
-fn part2() {
-    let pair_list = load_pairs();
-    let direct_neighbors = get_neighbors(&pair_list);
+fn part2(pair_list: &[(String, String)]) -> String {
+    let direct_neighbors = get_neighbors(pair_list);
 
     let mut complete_networks = vec![];
     for node in direct_neighbors.keys() {
@@ -67,14 +63,12 @@ fn part2() {
         find_networks(cur_members, potential_members, &direct_neighbors, &mut complete_networks);
     }
 
-    let longest = complete_networks
+    complete_networks
         .iter()
         .max_by_key(|network| network.len())
         .unwrap()
         .iter()
-        .join(",");
-
-    println!("Part 2: {longest}")
+        .join(",")
 }
 
 fn find_networks(
@@ -102,15 +96,15 @@ fn find_networks(
         // network's potential members and the newest member's neighbors
         // (helpfully, this always removes the newest member, who is never their own neighbor)
         let new_potential_members: HashSet<String> = potential_members.intersection(&neighbors)
-            .map(|s| s.clone())
+            .cloned()
             .collect();
 
         // if this doesn't leave anyone else we could add, the network can no longer grow
         if new_potential_members.is_empty() {
             new_current_members.sort();
             complete_networks.push(new_current_members);
-            if complete_networks.len() % 100 == 0 {
-                println!("Found {}", complete_networks.len());
+            if complete_networks.len().is_multiple_of(100) {
+                eprintln!("Found {}", complete_networks.len());
             }
         } else {
             // look recursively
@@ -119,23 +113,19 @@ fn find_networks(
     }
 }
 
-fn to_set(list: &Vec<String>) -> HashSet<String> {
+fn to_set(list: &[String]) -> HashSet<String> {
     list.iter()
-        .map(|s| s.clone())
+        .cloned()
         .collect()
 }
 
-fn load_pairs() -> Vec<(String, String)> {
-    fs::read_to_string("d23/input")
-        .unwrap()
+fn load_pairs(input: &str) -> ParseResult<Vec<(String, String)>> {
+    let source = Source::new(input);
+    input
         .lines()
         .map(|line| {
-            let pair: (String, String) = line
-                .split("-")
-                .map(|s| s.to_string())
-                .collect_tuple()
-                .unwrap();
-            pair
+            let [a, b] = source.split_exact(line, "-")?;
+            Ok((a.to_string(), b.to_string()))
         })
         .collect()
 }
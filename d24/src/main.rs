@@ -1,12 +1,13 @@
-use crate::RuleType::{AND, OR, XOR};
+use crate::RuleType::{And, Or, Xor};
 use itertools::Itertools;
+use libparse::{ParseResult, Source};
+use runner::Unsolved;
 use std::collections::HashMap;
-use std::fs;
 
 enum RuleType {
-    AND,
-    OR,
-    XOR
+    And,
+    Or,
+    Xor
 }
 
 struct Rule {
@@ -18,20 +19,22 @@ struct Rule {
 impl Rule {
     fn run(&self, a: bool, b: bool) -> bool {
         match self.rule_type {
-            AND => a && b,
-            OR => a || b,
-            XOR => a ^ b
+            And => a && b,
+            Or => a || b,
+            Xor => a ^ b
         }
     }
 }
 
+type Game = (HashMap<String, bool>, Vec<Rule>);
+
 fn main() {
-    part1();
-    part2();
+    runner::run("d24", load_game, part1, part2);
 }
 
-fn part1() {
-    let (mut vals, mut rules) = load_game();
+fn part1((vals, rules): &Game) -> i64 {
+    let mut vals = vals.clone();
+    let mut rules: Vec<&Rule> = rules.iter().collect();
 
     while !rules.is_empty() {
         let len = rules.len();
@@ -53,7 +56,7 @@ fn part1() {
         rules = unmatched_rules;
     }
 
-    let z_val: i64 = vals.iter()
+    vals.iter()
         .filter(|(key, _val)| key.starts_with('z'))
         .sorted_by_key(|(key, _val)| *key)
         .map(|(_key, value)| value)
@@ -65,58 +68,73 @@ fn part1() {
                 0
             }
         })
-        .sum();
-
-    println!("Part 1: {z_val}");
+        .sum()
 }
 
-fn part2() {
-
+fn part2(_game: &Game) -> Unsolved {
+    Unsolved
 }
 
-fn load_game() -> (HashMap<String, bool>, Vec<Rule>) {
-    let (input_str, rules_str) = fs::read_to_string("d24/input")
-        .unwrap()
-        .split("\n\n")
-        .map(|s| s.to_string())
-        .collect_tuple()
-        .unwrap();
+fn load_game(input: &str) -> ParseResult<Game> {
+    let source = Source::new(input);
+    let [input_str, rules_str] = source.sections_exact(input)?;
 
     let inputs = input_str.lines()
         .map(|line| {
-            let (name, val) = line.split(": ")
-                .collect_tuple()
-                .unwrap();
+            let (name, val) = source.key_value(line)?;
             let val = match val {
                 "0" => false,
                 "1" => true,
-                _ => panic!("Invalid bool {}", val)
+                _ => return Err(source.error(val, "0 or 1")),
             };
 
-            (name.to_string(), val)
+            Ok((name.to_string(), val))
         })
-        .collect();
+        .collect::<ParseResult<_>>()?;
 
     let rules = rules_str
         .lines()
         .map(|line| {
-            let (a, op, b, _, o) = line.split(" ").collect_tuple().unwrap();
-            Rule {
+            let [a, op, b, arrow, o] = source.split_exact(line, " ")?;
+            if arrow != "->" {
+                return Err(source.error(arrow, "\"->\""));
+            }
+            Ok(Rule {
                 inputs: (a.to_string(), b.to_string()),
-                rule_type: rule_from_str(op),
+                rule_type: rule_from_str(op).ok_or_else(|| source.error(op, "AND, OR, or XOR"))?,
                 output: o.to_string()
-            }
+            })
         })
-        .collect();
+        .collect::<ParseResult<_>>()?;
 
-    (inputs, rules)
+    Ok((inputs, rules))
 }
 
-fn rule_from_str(val: &str) -> RuleType {
+fn rule_from_str(val: &str) -> Option<RuleType> {
     match val {
-        "AND" => AND,
-        "OR" => OR,
-        "XOR" => XOR,
-        _ => panic!("Invalid rule type {val}")
+        "AND" => Some(And),
+        "OR" => Some(Or),
+        "XOR" => Some(Xor),
+        _ => None
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_gate() {
+        let input = "x00: 1\ny00: 0\n\nx00 AND y00 -> z00\nx00 NAND y00 -> z01\n";
+        let err = load_game(input).err().unwrap();
+        assert_eq!((err.line, err.column), (5, 5));
+        assert_eq!(err.to_string(), r#"5:5: expected AND, OR, or XOR, found "NAND""#);
+    }
+
+    #[test]
+    fn bad_wire_value() {
+        let input = "x00: 1\ny00: 2\n\nx00 AND y00 -> z00\n";
+        let err = load_game(input).err().unwrap();
+        assert_eq!((err.line, err.column), (2, 6));
+        assert_eq!(err.expected, "0 or 1");
+    }
+}
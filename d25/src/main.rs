@@ -1,32 +1,36 @@
-use std::fs;
+use libparse::{ParseResult, Source};
+use runner::Unsolved;
 
+// the ids only ever show up in the debug output
+#[allow(dead_code)]
 #[derive(Debug)]
 struct Key {
     heights: Vec<i32>,
     id: usize
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 struct Lock {
     pins: Vec<i32>,
     id: usize
 }
 
+type KeysAndLocks = (Vec<Key>, Vec<Lock>);
+
 fn main() {
-    part1();
-    part2();
+    runner::run("d25", get_locks, part1, part2);
 }
 
-fn part1() {
-    let (keys, locks) = get_locks();
+fn part1((keys, locks): &KeysAndLocks) -> usize {
     for key in keys.iter() {
-        println!("Key: {:?}", key);
+        eprintln!("Key: {:?}", key);
     }
     for lock in locks.iter() {
-        println!("Lock {:?}", lock);
+        eprintln!("Lock {:?}", lock);
     }
 
-    let fits: usize = locks.iter()
+    locks.iter()
         .map(|lock| {
             keys.iter()
                 .filter(|key| {
@@ -35,77 +39,136 @@ fn part1() {
                             return false;
                         }
                     }
-                    return true;
+                    true
                 })
                 .count()
         })
-        .sum();
-    
-    println!("Part 1: {fits}");
+        .sum()
 }
 
-fn part2() {
-
+fn part2(_keys_and_locks: &KeysAndLocks) -> Unsolved {
+    Unsolved
 }
 
-fn get_locks() -> (Vec<Key>, Vec<Lock>) {
+fn get_locks(input: &str) -> ParseResult<KeysAndLocks> {
+    let source = Source::new(input);
     let mut keys = vec![];
     let mut locks = vec![];
-    fs::read_to_string("d25/input")
-        .unwrap()
-        .split("\n\n")
-        .for_each(|blob| {
-            if blob.starts_with(".....") {
-                 keys.push(parse_key(blob));
-            }
-            if blob.starts_with("#####") {
-                locks.push(parse_lock(blob));
-            }
-        });
+    for blob in source.sections(input) {
+        check_schematic(&source, blob)?;
+        if blob.starts_with(".....") {
+            keys.push(parse_key(&source, blob)?);
+        } else if blob.starts_with("#####") {
+            locks.push(parse_lock(&source, blob)?);
+        } else {
+            return Err(source.error(blob, "a key (top row \".....\") or lock (top row \"#####\")"));
+        }
+    }
 
-    (keys, locks)
+    Ok((keys, locks))
 }
 
-fn parse_key(blob: &str) -> Key {
+/// Every schematic is 7 rows of 5 '#' or '.' tiles: keys are filled in along the bottom, and
+/// locks along the top with nothing along the bottom
+fn check_schematic(source: &Source, blob: &str) -> ParseResult<()> {
+    let rows: Vec<&str> = blob.lines().collect();
+    if rows.len() < 7 {
+        return Err(source.missing(blob, "7 rows per schematic"));
+    }
+    if rows.len() > 7 {
+        return Err(source.error(rows[7], "a blank line after 7 rows"));
+    }
+    for row in rows.iter() {
+        if row.len() != 5 || row.chars().any(|c| c != '#' && c != '.') {
+            return Err(source.error(row, "5 of '#' or '.'"));
+        }
+    }
+    match rows[0] {
+        "....." if rows[6] != "#####" => return Err(source.error(rows[6], "a filled in bottom row for a key")),
+        "#####" if rows[6] != "....." => return Err(source.error(rows[6], "an empty bottom row for a lock")),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn parse_key(source: &Source, blob: &str) -> ParseResult<Key> {
     let mut heights = vec![None, None, None, None, None];
     for (line_idx, line) in blob.lines().enumerate() {
         let line_height = 6 - line_idx as i32;
         for (char_idx, val) in line.chars().enumerate() {
-            if val == '#' {
-                if heights[char_idx] == None {
-                    heights[char_idx] = Some(line_height);
-                }
+            if val == '#' && heights[char_idx].is_none() {
+                heights[char_idx] = Some(line_height);
             }
         }
     }
 
-    let heights = heights.into_iter().map(|op| op.unwrap()).collect();
+    let heights: Vec<i32> = column_ends(source, blob, heights, "a '#' at the bottom of every key column")?;
     let id = parse_id(&heights);
-    Key { heights, id }
+    Ok(Key { heights, id })
 }
 
-fn parse_lock(blob: &str) -> Lock {
+fn parse_lock(source: &Source, blob: &str) -> ParseResult<Lock> {
     let mut pins = vec![None, None, None, None, None];
     for (line_idx, line) in blob.lines().enumerate() {
         let line_height = line_idx as i32 - 1;
         for (char_idx, val) in line.chars().enumerate() {
-            if val == '.' {
-                if pins[char_idx] == None {
-                    pins[char_idx] = Some(line_height);
-                }
+            if val == '.' && pins[char_idx].is_none() {
+                pins[char_idx] = Some(line_height);
             }
         }
     }
 
-    let pins = pins.into_iter().map(|op| op.unwrap()).collect();
+    let pins: Vec<i32> = column_ends(source, blob, pins, "a '.' at the bottom of every lock column")?;
     let id = parse_id(&pins);
-    Lock { pins, id }
+    Ok(Lock { pins, id })
+}
+
+/// Where each column's pin or key stopped, or an error at the bottom of a column that never did.
+fn column_ends(source: &Source, blob: &str, ends: Vec<Option<i32>>, expected: &str) -> ParseResult<Vec<i32>> {
+    let bottom = blob.lines().last().unwrap_or(blob);
+    ends.into_iter()
+        .enumerate()
+        .map(|(idx, end)| end.ok_or_else(|| source.error(&bottom[idx..idx + 1], expected)))
+        .collect()
 }
 
-fn parse_id(list: &Vec<i32>) -> usize {
-    let mut rev = list.clone();
+fn parse_id(list: &[i32]) -> usize {
+    let mut rev = list.to_vec();
     rev.reverse();
     rev.iter().enumerate()
         .map(|(i, val)| *val as usize * 10_usize.pow(i as u32))
         .sum()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = ".....\n#....\n#....\n#...#\n#.#.#\n#.###\n#####\n";
+
+    #[test]
+    fn fits() {
+        let lock = "#####\n.####\n.####\n.####\n.#.#.\n.#...\n.....\n";
+        let (keys, locks) = get_locks(&format!("{lock}\n{KEY}")).unwrap();
+        assert_eq!(locks[0].pins, vec![0, 5, 3, 4, 3]);
+        assert_eq!(keys[0].heights, vec![5, 0, 2, 1, 3]);
+        assert_eq!(part1(&(keys, locks)), 0);
+    }
+
+    #[test]
+    fn broken_schematics() {
+        // the second column never stops
+        let input = format!("{KEY}\n#####\n.####\n.####\n.####\n.#.#.\n.#...\n.#...\n");
+        let err = get_locks(&input).unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (15, 1, "an empty bottom row for a lock"));
+
+        let err = get_locks(".....\n#....\n#....\n#...#\n#.#.#\n#####\n####.\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (7, "a filled in bottom row for a key"));
+
+        let rows: Vec<&str> = "#####\n.####\n.####\n.####\n.#.#.\n.#...\n.#...".lines().collect();
+        let blob = rows.join("\n");
+        let err = parse_lock(&Source::new(&blob), &blob).unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (7, 2, "a '.' at the bottom of every lock column"));
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{Grid, Point2d};
+
+/// Which neighbours count as touching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left, and right
+    Four,
+    /// The diagonals too
+    Eight,
+}
+
+/// One connected run of equal cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Component<T> {
+    /// What every cell in it holds
+    pub value: T,
+    /// Number of cells
+    pub area: usize,
+    /// Top-left corner of the bounding box
+    pub min: Point2d<i32>,
+    /// Bottom-right corner of the bounding box, inclusive
+    pub max: Point2d<i32>,
+}
+
+/// Every cell's component, by index into `components`. Labels count up in the order their
+/// components first show up, row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Labels<T> {
+    pub grid: Grid<usize>,
+    pub components: Vec<Component<T>>,
+}
+
+/// Union-find over cell indexes, with path halving and union by size.
+struct DisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> DisjointSets {
+        DisjointSets { parent: (0..len).collect(), size: vec![1; len] }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            self.parent[index] = self.parent[self.parent[index]];
+            index = self.parent[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.size[a] >= self.size[b] { (a, b) } else { (b, a) };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+    }
+}
+
+/// Splits the grid into connected components of equal cells. No recursion, so one huge
+/// component is no trouble: each cell just joins up with the neighbours that came before it.
+pub fn label_components<T: Eq + Clone>(grid: &Grid<T>, connectivity: Connectivity) -> Labels<T> {
+    // the neighbours already visited in a row-by-row scan
+    let earlier: &[(i32, i32)] = match connectivity {
+        Connectivity::Four => &[(-1, 0), (0, -1)],
+        Connectivity::Eight => &[(-1, 0), (-1, -1), (0, -1), (1, -1)],
+    };
+
+    let mut sets = DisjointSets::new(grid.len());
+    for (point, cell) in grid.iter() {
+        let index = grid.index_of(point).unwrap();
+        for &(dx, dy) in earlier {
+            if let Some(neighbour) = grid.index_of(point + Point2d::new(dx, dy)) {
+                if grid[neighbour] == *cell {
+                    sets.union(index, neighbour);
+                }
+            }
+        }
+    }
+
+    // by root, which label it got
+    let mut label_of_root: Vec<Option<usize>> = vec![None; grid.len()];
+    let mut components: Vec<Component<T>> = vec![];
+    let mut labels = Grid::new(grid.width(), grid.height(), 0);
+    for (point, cell) in grid.iter() {
+        let index = grid.index_of(point).unwrap();
+        let root = sets.find(index);
+        let label = *label_of_root[root].get_or_insert_with(|| {
+            components.push(Component { value: cell.clone(), area: 0, min: point, max: point });
+            components.len() - 1
+        });
+
+        let component = &mut components[label];
+        component.area += 1;
+        component.min = Point2d::new(component.min.x.min(point.x), component.min.y.min(point.y));
+        component.max = Point2d::new(component.max.x.max(point.x), component.max.y.max(point.y));
+        labels[index] = label;
+    }
+
+    Labels { grid: labels, components }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid<char> {
+        Grid::from_rows(rows.iter().map(|row| row.chars().collect()).collect()).unwrap()
+    }
+
+    #[test]
+    fn four_and_eight() {
+        let grid = grid(&["AAB", "BAB", "BBA"]);
+
+        let four = label_components(&grid, Connectivity::Four);
+        assert_eq!(four.components.len(), 4);
+        assert_eq!(four.grid[Point2d::new(2, 2)], 3);
+        assert_eq!(
+            four.components[0],
+            Component { value: 'A', area: 3, min: Point2d::new(0, 0), max: Point2d::new(1, 1) }
+        );
+
+        let eight = label_components(&grid, Connectivity::Eight);
+        assert_eq!(eight.components.len(), 2);
+        assert_eq!(eight.components[0].area, 4);
+        assert_eq!(eight.components[1].area, 5);
+        assert_eq!(eight.components[1].max, Point2d::new(2, 2));
+    }
+
+    #[test]
+    fn one_big_component() {
+        let grid = Grid::new(1000, 1000, 7_u8);
+        let labels = label_components(&grid, Connectivity::Four);
+        assert_eq!(labels.components.len(), 1);
+        assert_eq!(labels.components[0].area, 1_000_000);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::Point2d;
+use std::ops::{Index, IndexMut};
+
+/// A dense, rectangular grid, stored row by row. Points are `Point2d<i32>` so they play nicely
+/// with `dir_delta`; anything outside the grid is simply absent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    /// None if the rows aren't all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+
+        Some(Grid { width, height, cells: rows.into_iter().flatten().collect() })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, point: Point2d<i32>) -> bool {
+        point.x >= 0 && point.y >= 0 && (point.x as usize) < self.width && (point.y as usize) < self.height
+    }
+
+    /// Where `point` lives in the underlying storage, if it's on the grid.
+    pub fn index_of(&self, point: Point2d<i32>) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn point_at(&self, index: usize) -> Point2d<i32> {
+        Point2d::new((index % self.width) as i32, (index / self.width) as i32)
+    }
+
+    pub fn get(&self, point: Point2d<i32>) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point2d<i32>) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.cells[index])
+    }
+
+    /// Every cell, with its point, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point2d<i32>, &T)> {
+        self.cells.iter().enumerate().map(|(index, cell)| (self.point_at(index), cell))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+}
+
+/// Panics if the point is off the grid.
+impl<T> Index<Point2d<i32>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2d<i32>) -> &T {
+        self.get(point).unwrap_or_else(|| panic!("{point:?} is off the grid"))
+    }
+}
+
+impl<T> IndexMut<Point2d<i32>> for Grid<T> {
+    fn index_mut(&mut self, point: Point2d<i32>) -> &mut T {
+        self.get_mut(point).unwrap_or_else(|| panic!("{point:?} is off the grid"))
+    }
+}
+
+/// Storage order, for when you've already got an index.
+impl<T> Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.cells[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.cells[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_and_indexes() {
+        let grid = Grid::from_rows(vec![vec!['a', 'b', 'c'], vec!['d', 'e', 'f']]).unwrap();
+        assert_eq!((grid.width(), grid.height(), grid.len()), (3, 2, 6));
+
+        assert_eq!(grid[Point2d::new(1, 1)], 'e');
+        assert_eq!(grid.get(Point2d::new(3, 0)), None);
+        assert_eq!(grid.get(Point2d::new(0, -1)), None);
+        assert_eq!(grid.index_of(Point2d::new(2, 1)), Some(5));
+        assert_eq!(grid.point_at(5), Point2d::new(2, 1));
+        assert_eq!(grid.iter().nth(4), Some((Point2d::new(1, 1), &'e')));
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert_eq!(Grid::from_rows(vec![vec![1, 2], vec![3]]), None);
+    }
+}
@@ -1,3 +1,9 @@
+mod components;
+mod grid;
+
+pub use components::{label_components, Component, Connectivity, Labels};
+pub use grid::Grid;
+
 use crate::Direction::*;
 use std::cmp::{max, min};
 use std::ops::{Add, Div, Mul, Rem, Sub};
@@ -126,6 +132,7 @@ pub fn dir_opposite(direction: Direction) -> Direction {
 
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_cast, clippy::useless_vec)]
 mod tests {
     use super::*;
 
@@ -0,0 +1,221 @@
+mod pieces;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A problem with an input file, pinned to the spot where it went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in characters
+    pub column: usize,
+    pub expected: String,
+    /// The offending text (never more than one line of it), or `None` if the input stopped short
+    pub found: Option<String>,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+impl ParseError {
+    pub fn in_file(self, file: impl Into<String>) -> ParseError {
+        ParseError { file: Some(file.into()), ..self }
+    }
+
+    /// The error message, followed by the offending line of `text` with the bad part underlined.
+    pub fn render(&self, text: &str) -> String {
+        let mut rendered = format!("{self}\n");
+        if let Some(line) = text.lines().nth(self.line - 1) {
+            let gutter = self.line.to_string().len();
+            let underline = self.found.as_ref().map_or(1, |found| found.chars().count().max(1));
+            rendered.push_str(&format!("{:gutter$} |\n", ""));
+            rendered.push_str(&format!("{} | {line}\n", self.line));
+            rendered.push_str(&format!(
+                "{:gutter$} | {}{}\n",
+                "",
+                " ".repeat(self.column - 1),
+                "^".repeat(underline)
+            ));
+        }
+        rendered
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        write!(f, "{}:{}: expected {}, found ", self.line, self.column, self.expected)?;
+        match &self.found {
+            Some(found) => write!(f, "{found:?}"),
+            None => write!(f, "nothing"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The full text of an input. Any `&str` sliced out of it (via `lines`, `split`, etc) can be
+/// turned back into a line and column, so loaders can keep slicing the input however they like
+/// and still report exactly where things went wrong.
+#[derive(Clone, Copy)]
+pub struct Source<'a> {
+    text: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Source<'a> {
+        Source { text }
+    }
+
+    /// 1-based line and column of the start of `at`, which must be a slice of this source.
+    pub fn position(&self, at: &str) -> (usize, usize) {
+        let before = &self.text[..self.offset(at)];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        let line = before.matches('\n').count() + 1;
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+
+    fn offset(&self, at: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let at_start = at.as_ptr() as usize;
+        if at_start < start || at_start > start + self.text.len() {
+            panic!("{at:?} isn't a slice of this source");
+        }
+        at_start - start
+    }
+
+    /// `at` was there, but it isn't what we wanted.
+    pub fn error(&self, at: &str, expected: impl Into<String>) -> ParseError {
+        let (line, column) = self.position(at);
+        let found = at.lines().next().filter(|found| !found.is_empty());
+
+        ParseError {
+            file: None,
+            line,
+            column,
+            expected: expected.into(),
+            found: found.map(|found| found.to_string()),
+        }
+    }
+
+    /// We ran off the end of `after` while still looking for something.
+    pub fn missing(&self, after: &str, expected: impl Into<String>) -> ParseError {
+        let (line, column) = self.position(&after[after.len()..]);
+
+        ParseError {
+            file: None,
+            line,
+            column,
+            expected: expected.into(),
+            found: None,
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self, at: &str, expected: &str) -> ParseResult<T> {
+        at.parse().map_err(|_| self.error(at, expected))
+    }
+
+    pub fn split_once(&self, at: &'a str, separator: &str) -> ParseResult<(&'a str, &'a str)> {
+        at.split_once(separator)
+            .ok_or_else(|| self.error(at, format!("two parts separated by {separator:?}")))
+    }
+
+    /// Splits `at` into exactly `N` parts.
+    pub fn split_exact<const N: usize>(&self, at: &'a str, separator: &str) -> ParseResult<[&'a str; N]> {
+        self.take(at, at.split(separator), &format!("{N} parts separated by {separator:?}"))
+    }
+
+    /// Takes exactly `N` items from `parts`, which must all be slices of `within`.
+    pub fn take<const N: usize, I>(&self, within: &'a str, parts: I, expected: &str) -> ParseResult<[&'a str; N]>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut parts = parts.into_iter();
+        let mut taken = [""; N];
+        for slot in taken.iter_mut() {
+            *slot = parts.next().ok_or_else(|| self.missing(within, expected))?;
+        }
+
+        match parts.next() {
+            Some(extra) => Err(self.error(extra, expected)),
+            None => Ok(taken),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "Register A: 729\nRegister B: oops\n\nProgram: 0,1,5";
+
+    #[test]
+    fn positions() {
+        let source = Source::new(TEXT);
+        let second_line = TEXT.lines().nth(1).unwrap();
+
+        assert_eq!(source.position(TEXT), (1, 1));
+        assert_eq!(source.position(second_line), (2, 1));
+        assert_eq!(source.position(&second_line[12..]), (2, 13));
+        assert_eq!(source.position(&TEXT[TEXT.len()..]), (4, 15));
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_bad_value() {
+        let source = Source::new(TEXT);
+        let second_line = TEXT.lines().nth(1).unwrap();
+        let (_, value) = source.split_once(second_line, ": ").unwrap();
+
+        let err = source.parse::<u64>(value, "a register value").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 13);
+        assert_eq!(err.found, Some(String::from("oops")));
+        assert_eq!(err.to_string(), r#"2:13: expected a register value, found "oops""#);
+        assert_eq!(
+            err.in_file("d17/input").to_string(),
+            r#"d17/input:2:13: expected a register value, found "oops""#
+        );
+    }
+
+    #[test]
+    fn take_exact() {
+        let source = Source::new(TEXT);
+        let last_line = TEXT.lines().last().unwrap();
+
+        let [name, program] = source.split_exact(last_line, ": ").unwrap();
+        assert_eq!((name, program), ("Program", "0,1,5"));
+
+        let too_few = source.split_exact::<4>(program, ",").unwrap_err();
+        assert_eq!((too_few.line, too_few.column), (4, 15));
+        assert_eq!(too_few.found, None);
+
+        let too_many = source.split_exact::<2>(program, ",").unwrap_err();
+        assert_eq!((too_many.line, too_many.column), (4, 14));
+        assert_eq!(too_many.found, Some(String::from("5")));
+    }
+
+    #[test]
+    fn render_underlines_the_problem() {
+        let source = Source::new(TEXT);
+        let second_line = TEXT.lines().nth(1).unwrap();
+        let err = source.error(&second_line[12..], "a number");
+
+        assert_eq!(
+            err.render(TEXT),
+            "2:13: expected a number, found \"oops\"\n  |\n2 | Register B: oops\n  |             ^^^^\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn slices_must_come_from_the_source() {
+        let other = String::from("elsewhere");
+        Source::new(TEXT).position(&other);
+    }
+}
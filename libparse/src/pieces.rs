@@ -0,0 +1,158 @@
+use crate::{ParseResult, Source};
+use lib2d::Point2d;
+use std::str::FromStr;
+
+/// The shapes that keep turning up in puzzle inputs. Everything hands back slices of (or values
+/// parsed from) the source, so errors still point at the right spot.
+impl<'a> Source<'a> {
+    /// Blocks of lines separated by blank lines, without their trailing newlines.
+    pub fn sections(&self, at: &'a str) -> impl Iterator<Item = &'a str> {
+        at.split("\n\n")
+            .map(|section| section.trim_matches('\n'))
+            .filter(|section| !section.is_empty())
+    }
+
+    pub fn sections_exact<const N: usize>(&self, at: &'a str) -> ParseResult<[&'a str; N]> {
+        self.take(at, self.sections(at), &format!("{N} sections separated by blank lines"))
+    }
+
+    /// Splits a `key: value` line.
+    pub fn key_value(&self, line: &'a str) -> ParseResult<(&'a str, &'a str)> {
+        self.split_once(line, ": ")
+    }
+
+    /// The value from a `key: value` line, which has to have the given key.
+    pub fn field(&self, line: &'a str, key: &str) -> ParseResult<&'a str> {
+        match line.strip_prefix(key).and_then(|rest| rest.strip_prefix(": ")) {
+            Some(value) => Ok(value),
+            None => Err(self.error(line, format!("\"{key}: ...\""))),
+        }
+    }
+
+    /// Values separated by commas and/or whitespace.
+    pub fn list<T: FromStr>(&self, at: &'a str, expected: &str) -> ParseResult<Vec<T>> {
+        words(at).map(|word| self.parse(word, expected)).collect()
+    }
+
+    pub fn list_exact<T: FromStr, const N: usize>(&self, at: &'a str, expected: &str) -> ParseResult<[T; N]> {
+        let words: [&str; N] = self.take(at, words(at), &format!("{N} of {expected}"))?;
+        self.parse_all(&words, expected)
+    }
+
+    /// Every integer in `at`, whatever's around it: `p=0,4 v=3,-3` has 0, 4, 3, and -3.
+    /// A `-` directly in front of the digits makes it negative.
+    pub fn integers<T: FromStr>(&self, at: &'a str, expected: &str) -> ParseResult<Vec<T>> {
+        integers(at).map(|int| self.parse(int, expected)).collect()
+    }
+
+    pub fn integers_exact<T: FromStr, const N: usize>(&self, at: &'a str, expected: &str) -> ParseResult<[T; N]> {
+        let ints: [&str; N] = self.take(at, integers(at), &format!("{N} of {expected}"))?;
+        self.parse_all(&ints, expected)
+    }
+
+    /// An `x,y` pair.
+    pub fn point<T: FromStr>(&self, at: &'a str, expected: &str) -> ParseResult<Point2d<T>> {
+        let [x, y] = self.split_exact(at, ",")?;
+        Ok(Point2d::new(self.parse(x.trim(), expected)?, self.parse(y.trim(), expected)?))
+    }
+
+    fn parse_all<T: FromStr, const N: usize>(&self, parts: &[&str; N], expected: &str) -> ParseResult<[T; N]> {
+        let values: Vec<T> = parts.iter().map(|part| self.parse(part, expected)).collect::<ParseResult<_>>()?;
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(_) => unreachable!("parsed exactly {N} values"),
+        }
+    }
+}
+
+fn words(at: &str) -> impl Iterator<Item = &str> {
+    at.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+}
+
+fn integers(at: &str) -> impl Iterator<Item = &str> {
+    let bytes = at.as_bytes();
+    let mut idx = 0;
+    std::iter::from_fn(move || {
+        while idx < bytes.len() {
+            let negative = bytes[idx] == b'-' && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit);
+            if negative || bytes[idx].is_ascii_digit() {
+                let start = idx;
+                idx += 1;
+                while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+                return Some(&at[start..idx]);
+            }
+            idx += 1;
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        let text = "a\nb\n\nc\n\n\nd\n";
+        let source = Source::new(text);
+
+        assert_eq!(source.sections(text).collect::<Vec<_>>(), vec!["a\nb", "c", "d"]);
+        let err = source.sections_exact::<2>(text).unwrap_err();
+        assert_eq!((err.line, err.column), (7, 1));
+        assert_eq!(err.found, Some(String::from("d")));
+    }
+
+    #[test]
+    fn fields() {
+        let text = "Register A: 729\nProgram: 0,1,5";
+        let source = Source::new(text);
+        let [register, program] = source.take(text, text.lines(), "2 lines").unwrap();
+
+        assert_eq!(source.key_value(register).unwrap(), ("Register A", "729"));
+        assert_eq!(source.field(register, "Register A").unwrap(), "729");
+        assert_eq!(source.field(register, "Register B").unwrap_err().expected, "\"Register B: ...\"");
+
+        let program = source.field(program, "Program").unwrap();
+        assert_eq!(source.list::<u8>(program, "an opcode").unwrap(), vec![0, 1, 5]);
+    }
+
+    #[test]
+    fn lists() {
+        let text = "3   4\n7 6 4 2,1\n";
+        let source = Source::new(text);
+
+        let first = text.lines().next().unwrap();
+        assert_eq!(source.list_exact::<u32, 2>(first, "an id").unwrap(), [3, 4]);
+        assert_eq!(source.list::<u32>(text, "an id").unwrap(), vec![3, 4, 7, 6, 4, 2, 1]);
+
+        let err = source.list_exact::<u32, 3>(first, "an id").unwrap_err();
+        assert_eq!(err.expected, "3 of an id");
+    }
+
+    #[test]
+    fn embedded_integers() {
+        let text = "p=0,4 v=3,-3\nButton A: X+94, Y-34 a-b";
+        let source = Source::new(text);
+        let [bot, button] = source.take(text, text.lines(), "2 lines").unwrap();
+
+        assert_eq!(source.integers_exact::<i32, 4>(bot, "a number").unwrap(), [0, 4, 3, -3]);
+        assert_eq!(source.integers::<i64>(button, "a number").unwrap(), vec![94, -34]);
+
+        let err = source.integers::<u8>(bot, "a speed").unwrap_err();
+        assert_eq!((err.line, err.column, err.found), (1, 11, Some(String::from("-3"))));
+    }
+
+    #[test]
+    fn points() {
+        let text = "6,1\n2,x";
+        let source = Source::new(text);
+        let [good, bad] = source.take(text, text.lines(), "2 lines").unwrap();
+
+        assert_eq!(source.point::<i32>(good, "a coordinate").unwrap(), Point2d::new(6, 1));
+        let err = source.point::<i32>(bad, "a coordinate").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+}
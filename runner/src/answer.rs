@@ -0,0 +1,143 @@
+use std::fmt::{Display, Formatter};
+
+/// Stand-in answer for a part that hasn't been solved yet.
+pub struct Unsolved;
+
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsolved")
+    }
+}
+
+/// Whatever a part came up with. Parts return their own types; anything that converts into an
+/// `Answer` will do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    Tuple(Vec<Answer>),
+    Unsolved,
+}
+
+impl Answer {
+    pub fn to_json(&self) -> String {
+        match self {
+            Answer::Integer(n) => n.to_string(),
+            Answer::Text(text) => json_string(text),
+            Answer::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_json()).collect();
+                format!("[{}]", items.join(","))
+            }
+            Answer::Unsolved => String::from("null"),
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Integer(n) => write!(f, "{n}"),
+            Answer::Text(text) => write!(f, "{text}"),
+            Answer::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "({})", items.join(", "))
+            }
+            Answer::Unsolved => write!(f, "{Unsolved}"),
+        }
+    }
+}
+
+macro_rules! integer_answers {
+    ($($int:ty),*) => {
+        $(impl From<$int> for Answer {
+            fn from(n: $int) -> Self {
+                Answer::Integer(n as i128)
+            }
+        })*
+    };
+}
+
+integer_answers!(i32, i64, i128, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
+
+impl From<Unsolved> for Answer {
+    fn from(_: Unsolved) -> Self {
+        Answer::Unsolved
+    }
+}
+
+/// No answer at all counts as unsolved.
+impl<T: Into<Answer>> From<Option<T>> for Answer {
+    fn from(answer: Option<T>) -> Self {
+        answer.map_or(Answer::Unsolved, |answer| answer.into())
+    }
+}
+
+macro_rules! tuple_answers {
+    ($(($($name:ident),+)),*) => {
+        $(impl<$($name: Into<Answer>),+> From<($($name,)+)> for Answer {
+            #[allow(non_snake_case)]
+            fn from(($($name,)+): ($($name,)+)) -> Self {
+                Answer::Tuple(vec![$($name.into()),+])
+            }
+        })*
+    };
+}
+
+tuple_answers!((A, B), (A, B, C), (A, B, C, D));
+
+pub fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Answer::from(42_usize), Answer::Integer(42));
+        assert_eq!(Answer::from(-7_i64), Answer::Integer(-7));
+        assert_eq!(Answer::from("co,de,ka,ta"), Answer::Text(String::from("co,de,ka,ta")));
+        assert_eq!(Answer::from(Unsolved), Answer::Unsolved);
+        assert_eq!(Answer::from(None::<u64>), Answer::Unsolved);
+        assert_eq!(
+            Answer::from(((-2, 1, -1, 3), 23_i64)),
+            Answer::Tuple(vec![
+                Answer::Tuple(vec![Answer::Integer(-2), Answer::Integer(1), Answer::Integer(-1), Answer::Integer(3)]),
+                Answer::Integer(23),
+            ])
+        );
+    }
+
+    #[test]
+    fn formats() {
+        let answer = Answer::from(((-2, 1), String::from("say \"hi\"")));
+        assert_eq!(answer.to_string(), r#"((-2, 1), say "hi")"#);
+        assert_eq!(answer.to_json(), r#"[[-2,1],"say \"hi\""]"#);
+        assert_eq!(Answer::Unsolved.to_json(), "null");
+        assert_eq!(Answer::Unsolved.to_string(), "unsolved");
+    }
+}
@@ -0,0 +1,208 @@
+use crate::answer::json_string;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::ErrorKind;
+use std::path::Path;
+use std::{fs, io};
+
+pub const DEFAULT_ANSWERS: &str = "answers.toml";
+
+/// Known-good answers, keyed by day, then input hash, then part. Lives on disk as a (very small
+/// subset of) TOML:
+///
+/// ```toml
+/// [d01.5f3a9c0e4b7d2a61]
+/// part1 = "1722302"
+/// part2 = "20373490"
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct AnswerStore {
+    answers: BTreeMap<(String, String), BTreeMap<String, String>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Match,
+    Mismatch(String),
+    New,
+    /// NEW, and now saved to the answers file
+    Recorded,
+}
+
+impl Verdict {
+    /// The verdict (and expected answer, on a mismatch) as fields for a JSON object
+    pub fn to_json_fields(&self) -> String {
+        match self {
+            Verdict::Match => String::from(r#""verdict":"MATCH""#),
+            Verdict::Mismatch(expected) => format!(r#""verdict":"MISMATCH","expected":{}"#, json_string(expected)),
+            Verdict::New => String::from(r#""verdict":"NEW""#),
+            Verdict::Recorded => String::from(r#""verdict":"NEW","recorded":true"#),
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Match => write!(f, "MATCH"),
+            Verdict::Mismatch(expected) => write!(f, "MISMATCH, expected {expected}"),
+            Verdict::New => write!(f, "NEW"),
+            Verdict::Recorded => write!(f, "NEW, recorded"),
+        }
+    }
+}
+
+impl AnswerStore {
+    /// A missing file is just an empty store.
+    pub fn load(path: &Path) -> Result<AnswerStore, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => AnswerStore::parse(&text).map_err(|err| format!("{}:{err}", path.display())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(AnswerStore::default()),
+            Err(err) => Err(format!("couldn't read {}: {err}", path.display())),
+        }
+    }
+
+    fn parse(text: &str) -> Result<AnswerStore, String> {
+        let mut store = AnswerStore::default();
+        let mut table: Option<(String, String)> = None;
+
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let bad_line = |what: &str| format!("{}: {what}: {line:?}", idx + 1);
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                let (day, hash) = header.split_once('.').ok_or_else(|| bad_line("expected [day.hash]"))?;
+                table = Some((day.to_string(), hash.to_string()));
+                continue;
+            }
+
+            let key = table.clone().ok_or_else(|| bad_line("answer outside of a [day.hash] table"))?;
+            let (part, answer) = line.split_once(" = ").ok_or_else(|| bad_line("expected part = \"answer\""))?;
+            let answer = unquote(answer).ok_or_else(|| bad_line("answers must be quoted strings"))?;
+            store.answers.entry(key).or_default().insert(part.to_string(), answer);
+        }
+
+        Ok(store)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_toml())
+    }
+
+    fn to_toml(&self) -> String {
+        let tables: Vec<String> = self.answers.iter()
+            .map(|((day, hash), parts)| {
+                let mut table = format!("[{day}.{hash}]\n");
+                for (part, answer) in parts {
+                    table.push_str(&format!("{part} = {}\n", quote(answer)));
+                }
+                table
+            })
+            .collect();
+
+        tables.join("\n")
+    }
+
+    pub fn get(&self, day: &str, hash: &str, part: &str) -> Option<&str> {
+        self.answers
+            .get(&(day.to_string(), hash.to_string()))
+            .and_then(|parts| parts.get(part))
+            .map(|answer| answer.as_str())
+    }
+
+    pub fn check(&self, day: &str, hash: &str, part: &str, answer: &str) -> Verdict {
+        match self.get(day, hash, part) {
+            Some(expected) if expected == answer => Verdict::Match,
+            Some(expected) => Verdict::Mismatch(expected.to_string()),
+            None => Verdict::New,
+        }
+    }
+
+    pub fn record(&mut self, day: &str, hash: &str, part: &str, answer: &str) {
+        self.answers
+            .entry((day.to_string(), hash.to_string()))
+            .or_default()
+            .insert(part.to_string(), answer.to_string());
+    }
+}
+
+/// FNV-1a; answers only need to follow the input around, not survive an adversary. Day options
+/// can change the answers too, so they're part of the hash (when there are any).
+pub fn input_hash(input: &str, day_args: &[String]) -> String {
+    let fnv = |hash: u64, byte: u8| (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+
+    let mut hash = input.bytes().fold(0xcbf29ce484222325_u64, fnv);
+    for arg in day_args {
+        hash = fnv(hash, 0);
+        hash = arg.bytes().fold(hash, fnv);
+    }
+    format!("{hash:016x}")
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            '"' => return None,
+            _ => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verdicts() {
+        let mut store = AnswerStore::default();
+        store.record("d01", "abc", "part1", "11");
+
+        assert_eq!(store.check("d01", "abc", "part1", "11"), Verdict::Match);
+        assert_eq!(store.check("d01", "abc", "part1", "12"), Verdict::Mismatch(String::from("11")));
+        assert_eq!(store.check("d01", "abc", "part2", "31"), Verdict::New);
+        // a different input has different answers
+        assert_eq!(store.check("d01", "def", "part1", "11"), Verdict::New);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut store = AnswerStore::default();
+        store.record("d17", "0123", "part1", "4,6,3,5,6,3,5,2,1,0");
+        store.record("d22", "4567", "part2", r#"(-2, 1, -1, 3) - "23""#);
+        store.record("d01", "89ab", "part2", "31");
+
+        let toml = store.to_toml();
+        assert!(toml.starts_with("[d01.89ab]\npart2 = \"31\"\n\n[d17.0123]\n"));
+        assert_eq!(AnswerStore::parse(&toml).unwrap(), store);
+    }
+
+    #[test]
+    fn rejects_nonsense() {
+        assert!(AnswerStore::parse("part1 = \"1\"").is_err());
+        assert!(AnswerStore::parse("[d01]\npart1 = \"1\"").is_err());
+        assert!(AnswerStore::parse("[d01.abc]\npart1 = 1").is_err());
+    }
+
+    #[test]
+    fn hashes() {
+        assert_eq!(input_hash("", &[]), "cbf29ce484222325");
+        assert_eq!(input_hash("a", &[]), "af63dc4c8601ec8c");
+        assert_ne!(input_hash("3   4\n", &[]), input_hash("3   4", &[]));
+
+        let args = [String::from("--policy"), String::from("left")];
+        assert_ne!(input_hash("a", &args), input_hash("a", &[]));
+        assert_ne!(input_hash("a", &args), input_hash("a", &[String::from("--policyleft")]));
+    }
+}
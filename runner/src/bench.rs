@@ -0,0 +1,176 @@
+use std::fs::OpenOptions;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+pub const CSV_HEADER: &str = "day,phase,iterations,min_ns,median_ns,mean_ns,max_ns";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        }
+    }
+
+    /// Which part this is; parse isn't one.
+    pub fn number(&self) -> usize {
+        match self {
+            Phase::Parse => 0,
+            Phase::Part1 => 1,
+            Phase::Part2 => 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Timing {
+    pub day: String,
+    pub phase: Phase,
+    // always sorted, fastest first
+    samples: Vec<Duration>,
+}
+
+impl Timing {
+    pub fn measure<T>(day: &str, phase: Phase, iterations: usize, mut f: impl FnMut() -> T) -> Timing {
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let result = f();
+            samples.push(start.elapsed());
+            // don't let the optimizer decide the answer wasn't needed; drop it outside the timer
+            black_box(result);
+        }
+
+        Timing::from_samples(day, phase, samples)
+    }
+
+    fn from_samples(day: &str, phase: Phase, mut samples: Vec<Duration>) -> Timing {
+        samples.sort();
+        Timing { day: day.to_string(), phase, samples }
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.samples[0]
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples[self.samples.len() - 1]
+    }
+
+    pub fn median(&self) -> Duration {
+        self.samples[self.samples.len() / 2]
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    pub fn to_text(&self) -> String {
+        format!(
+            "{} {:<5} median {:>10.2?}  min {:>10.2?}  max {:>10.2?}  ({} runs)",
+            self.day,
+            self.phase.name(),
+            self.median(),
+            self.min(),
+            self.max(),
+            self.iterations()
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"day":"{}","phase":"{}","iterations":{},"min_ns":{},"median_ns":{},"mean_ns":{},"max_ns":{}}}"#,
+            self.day,
+            self.phase.name(),
+            self.iterations(),
+            self.min().as_nanos(),
+            self.median().as_nanos(),
+            self.mean().as_nanos(),
+            self.max().as_nanos()
+        )
+    }
+
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.day,
+            self.phase.name(),
+            self.iterations(),
+            self.min().as_nanos(),
+            self.median().as_nanos(),
+            self.mean().as_nanos(),
+            self.max().as_nanos()
+        )
+    }
+}
+
+/// Appends the timings to a results file, so several days can share one file.
+/// Files ending in `.csv` get CSV (with a header if the file is new); anything else gets JSON lines.
+pub fn append_results(path: &Path, timings: &[Timing]) -> io::Result<()> {
+    let csv = path.extension().is_some_and(|ext| ext == "csv");
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if csv && file.metadata()?.len() == 0 {
+        writeln!(file, "{CSV_HEADER}")?;
+    }
+    for timing in timings {
+        if csv {
+            writeln!(file, "{}", timing.to_csv())?;
+        } else {
+            writeln!(file, "{}", timing.to_json())?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let samples = [40, 10, 30, 20].iter().map(|n| Duration::from_nanos(*n)).collect();
+        let timing = Timing::from_samples("d01", Phase::Part1, samples);
+
+        assert_eq!(timing.iterations(), 4);
+        assert_eq!(timing.min(), Duration::from_nanos(10));
+        assert_eq!(timing.median(), Duration::from_nanos(30));
+        assert_eq!(timing.mean(), Duration::from_nanos(25));
+        assert_eq!(timing.max(), Duration::from_nanos(40));
+    }
+
+    #[test]
+    fn formats() {
+        let samples = vec![Duration::from_nanos(5)];
+        let timing = Timing::from_samples("d01", Phase::Parse, samples);
+
+        assert_eq!(timing.to_csv(), "d01,parse,1,5,5,5,5");
+        assert_eq!(
+            timing.to_json(),
+            r#"{"day":"d01","phase":"parse","iterations":1,"min_ns":5,"median_ns":5,"mean_ns":5,"max_ns":5}"#
+        );
+    }
+
+    #[test]
+    fn measure_runs_every_iteration() {
+        let mut calls = 0;
+        let timing = Timing::measure("d01", Phase::Part2, 7, || calls += 1);
+        assert_eq!(calls, 7);
+        assert_eq!(timing.iterations(), 7);
+    }
+}
@@ -0,0 +1,188 @@
+mod answer;
+mod answers;
+mod bench;
+mod options;
+
+pub use answer::{Answer, Unsolved};
+pub use answers::{input_hash, AnswerStore, Verdict, DEFAULT_ANSWERS};
+pub use bench::{append_results, Phase, Timing, CSV_HEADER};
+pub use options::{Options, USAGE};
+
+use answer::json_string;
+use libparse::ParseResult;
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+/// Entry point for every day: reads the input, parses it once, and either prints both answers
+/// (checked against the answers file) or (with `--bench`) times each phase separately. Bad input
+/// gets reported (with the offending line) before anything else runs.
+///
+/// Only answers go to stdout, as text or (with `--json`) one JSON object per part; anything else a
+/// day wants to say belongs on stderr.
+pub fn run<I, A, B>(
+    day: &str,
+    parse: impl Fn(&str) -> ParseResult<I>,
+    part1: impl Fn(&I) -> A,
+    part2: impl Fn(&I) -> B,
+) where
+    A: Into<Answer>,
+    B: Into<Answer>,
+{
+    let options = match Options::parse(day, env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{day}: {message}");
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    let input = match fs::read_to_string(&options.input) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{day}: couldn't read {}: {err}", options.input.display());
+            process::exit(1);
+        }
+    };
+
+    let parsed = match parse(&input) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            let err = err.in_file(options.input.display().to_string());
+            eprint!("{day}: {}", err.render(&input));
+            process::exit(1);
+        }
+    };
+
+    let Some(iterations) = options.bench else {
+        let mut checker = AnswerChecker::new(day, &options, &input);
+        checker.solve(Phase::Part1, || part1(&parsed).into());
+        checker.solve(Phase::Part2, || part2(&parsed).into());
+        checker.finish();
+        return;
+    };
+
+    // parse gets timed on its own; the parts share a single parsed input
+    let parse_timing = Timing::measure(day, Phase::Parse, iterations, || parse(&input));
+    let timings = [
+        parse_timing,
+        Timing::measure(day, Phase::Part1, iterations, || part1(&parsed)),
+        Timing::measure(day, Phase::Part2, iterations, || part2(&parsed)),
+    ];
+
+    for timing in timings.iter() {
+        if options.json {
+            println!("{}", timing.to_json());
+        } else {
+            println!("{}", timing.to_text());
+        }
+    }
+
+    if let Some(out) = options.out {
+        if let Err(err) = append_results(&out, &timings) {
+            eprintln!("{day}: couldn't write {}: {err}", out.display());
+            process::exit(1);
+        }
+    }
+}
+
+/// The options after `--`, which belong to the day itself.
+pub fn day_args() -> Vec<String> {
+    env::args().skip_while(|arg| arg != "--").skip(1).collect()
+}
+
+/// For days that don't like their options.
+pub fn bad_day_args(day: &str, message: &str) -> ! {
+    eprintln!("{day}: {message}");
+    eprintln!("{USAGE}");
+    process::exit(2);
+}
+
+/// Prints each answer alongside how it compares to the answers file.
+struct AnswerChecker<'a> {
+    day: &'a str,
+    options: &'a Options,
+    hash: String,
+    store: AnswerStore,
+    mismatched: bool,
+    recorded: bool,
+}
+
+impl<'a> AnswerChecker<'a> {
+    fn new(day: &'a str, options: &'a Options, input: &str) -> AnswerChecker<'a> {
+        let store = match AnswerStore::load(&options.answers) {
+            Ok(store) => store,
+            Err(message) => {
+                eprintln!("{day}: {message}");
+                process::exit(1);
+            }
+        };
+
+        let hash = input_hash(input, &options.day_args);
+        AnswerChecker { day, options, hash, store, mismatched: false, recorded: false }
+    }
+
+    fn solve(&mut self, phase: Phase, part: impl FnOnce() -> Answer) {
+        let start = Instant::now();
+        let answer = part();
+        let elapsed = start.elapsed();
+
+        let verdict = self.check(phase, &answer);
+        if self.options.json {
+            println!("{}", self.to_json(phase, &answer, elapsed, verdict.as_ref()));
+        } else {
+            match verdict {
+                Some(verdict) => println!("Part {}: {answer}  [{verdict}]", phase.number()),
+                None => println!("Part {}: {answer}", phase.number()),
+            }
+        }
+    }
+
+    /// No verdict for a part that's never been solved.
+    fn check(&mut self, phase: Phase, answer: &Answer) -> Option<Verdict> {
+        let answer_text = answer.to_string();
+        let verdict = self.store.check(self.day, &self.hash, phase.name(), &answer_text);
+        match verdict {
+            Verdict::New if *answer == Answer::Unsolved => None,
+            Verdict::New if self.options.record => {
+                self.store.record(self.day, &self.hash, phase.name(), &answer_text);
+                self.recorded = true;
+                Some(Verdict::Recorded)
+            }
+            Verdict::Mismatch(_) => {
+                self.mismatched = true;
+                Some(verdict)
+            }
+            _ => Some(verdict),
+        }
+    }
+
+    fn to_json(&self, phase: Phase, answer: &Answer, elapsed: Duration, verdict: Option<&Verdict>) -> String {
+        let mut json = format!(
+            r#"{{"day":{},"part":{},"answer":{},"elapsed_ns":{}"#,
+            json_string(self.day),
+            phase.number(),
+            answer.to_json(),
+            elapsed.as_nanos()
+        );
+        if let Some(verdict) = verdict {
+            json.push(',');
+            json.push_str(&verdict.to_json_fields());
+        }
+        json.push('}');
+        json
+    }
+
+    /// Saves anything recorded, and fails the run if an answer changed.
+    fn finish(self) {
+        if self.recorded {
+            if let Err(err) = self.store.save(&self.options.answers) {
+                eprintln!("{}: couldn't write {}: {err}", self.day, self.options.answers.display());
+                process::exit(1);
+            }
+        }
+        if self.mismatched {
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Runs each day's binary in turn, forwarding `--bench` and `--out` so that a whole suite of
+//! timings lands in a single results file, or `--answers`/`--record` so every day gets checked
+//! against the same answers file.
+//!
+//! `cargo build --release --workspace && target/release/runner --bench 5 --out bench.csv`
+
+use std::path::PathBuf;
+use std::process::{exit, Command, Stdio};
+use std::{env, fs};
+
+fn main() {
+    let mut days: Vec<String> = vec![];
+    let mut forwarded: Vec<String> = vec![];
+    let mut out: Option<PathBuf> = None;
+
+    let mut args = env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bench" => {
+                forwarded.push(arg);
+                if let Some(iterations) = args.next_if(|n| n.parse::<usize>().is_ok()) {
+                    forwarded.push(iterations);
+                }
+            }
+            "--record" | "--json" => forwarded.push(arg),
+            "--answers" => match args.next() {
+                Some(path) => forwarded.extend([arg, path]),
+                None => fail("--answers needs a path"),
+            },
+            "--out" => match args.next() {
+                Some(path) => out = Some(PathBuf::from(path)),
+                None => fail("--out needs a path"),
+            },
+            day if is_day(day) => days.push(arg),
+            _ => fail(&format!("Unrecognized argument {arg:?}")),
+        }
+    }
+
+    if days.is_empty() {
+        days = (1..=25).map(|day| format!("d{day:02}")).collect();
+    }
+
+    if let Some(out) = out {
+        // every day appends to the results file, so start from a clean slate
+        if out.exists() {
+            if let Err(err) = fs::remove_file(&out) {
+                fail(&format!("couldn't clear {}: {err}", out.display()));
+            }
+        }
+        forwarded.push(String::from("--out"));
+        forwarded.push(out.to_string_lossy().to_string());
+    }
+
+    // the day binaries get built right next to this one
+    let bin_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| fail("couldn't find the directory holding the day binaries"));
+
+    let mut failures = vec![];
+    for day in days.iter() {
+        let exe = bin_dir.join(format!("{day}{}", env::consts::EXE_SUFFIX));
+        if !exe.exists() {
+            eprintln!("{day}: no binary at {}; run `cargo build --workspace` first", exe.display());
+            failures.push(day);
+            continue;
+        }
+
+        // JSON lines already say which day they're from; a header would just get in the way
+        if !forwarded.iter().any(|arg| arg == "--json") {
+            println!("== {day} ==");
+        }
+        let status = Command::new(&exe)
+            .args(&forwarded)
+            .stdin(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                eprintln!("{day}: exited with {status}");
+                failures.push(day);
+            }
+            Err(err) => {
+                eprintln!("{day}: couldn't start {}: {err}", exe.display());
+                failures.push(day);
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        eprintln!("Failed: {failures:?}");
+        exit(1);
+    }
+}
+
+fn is_day(arg: &str) -> bool {
+    arg.len() == 3 && arg.starts_with('d') && arg[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("runner: {message}");
+    eprintln!(
+        "usage: runner [--answers ANSWERS.toml] [--record] [--json] [--bench [ITERATIONS]] [--out RESULTS.json|RESULTS.csv] [dNN...]"
+    );
+    exit(2);
+}
@@ -0,0 +1,131 @@
+use crate::answers::DEFAULT_ANSWERS;
+use std::path::PathBuf;
+
+const DEFAULT_ITERATIONS: usize = 3;
+
+pub const USAGE: &str =
+    "usage: [--input PATH] [--answers ANSWERS.toml] [--record] [--json] [--bench [ITERATIONS]] [--out RESULTS.json|RESULTS.csv] [-- DAY OPTIONS...]";
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub input: PathBuf,
+    pub bench: Option<usize>,
+    pub out: Option<PathBuf>,
+    pub answers: PathBuf,
+    /// Save any NEW answers to the answers file
+    pub record: bool,
+    /// Print one JSON object per line instead of text
+    pub json: bool,
+    /// Everything after `--`, for the day itself to make sense of
+    pub day_args: Vec<String>,
+}
+
+impl Options {
+    pub fn parse<I>(day: &str, args: I) -> Result<Options, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Options {
+            input: PathBuf::from(format!("{day}/input")),
+            bench: None,
+            out: None,
+            answers: PathBuf::from(DEFAULT_ANSWERS),
+            record: false,
+            json: false,
+            day_args: vec![],
+        };
+
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" => {
+                    let path = args.next().ok_or("--input needs a path")?;
+                    options.input = PathBuf::from(path);
+                }
+                "--bench" => {
+                    // the iteration count is optional, so only eat the next arg if it's a number
+                    let iterations = match args.peek().and_then(|n| n.parse().ok()) {
+                        Some(iterations) => {
+                            args.next();
+                            iterations
+                        }
+                        None => DEFAULT_ITERATIONS,
+                    };
+                    if iterations == 0 {
+                        return Err(String::from("--bench needs at least 1 iteration"));
+                    }
+                    options.bench = Some(iterations);
+                }
+                "--out" => {
+                    let path = args.next().ok_or("--out needs a path")?;
+                    options.out = Some(PathBuf::from(path));
+                }
+                "--answers" => {
+                    let path = args.next().ok_or("--answers needs a path")?;
+                    options.answers = PathBuf::from(path);
+                }
+                "--record" => options.record = true,
+                "--json" => options.json = true,
+                "--" => options.day_args.extend(args.by_ref()),
+                _ => return Err(format!("Unrecognized argument {arg:?}")),
+            }
+        }
+
+        if options.out.is_some() && options.bench.is_none() {
+            return Err(String::from("--out only makes sense with --bench"));
+        }
+        if options.record && options.bench.is_some() {
+            return Err(String::from("--record doesn't work with --bench, which doesn't check answers"));
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse("d06", args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.input, PathBuf::from("d06/input"));
+        assert_eq!(options.bench, None);
+        assert_eq!(options.out, None);
+        assert_eq!(options.answers, PathBuf::from(DEFAULT_ANSWERS));
+        assert!(!options.record);
+        assert!(!options.json);
+        assert!(options.day_args.is_empty());
+    }
+
+    #[test]
+    fn day_args_pass_through() {
+        let options = parse(&["--json", "--", "--policy", "left", "--json"]).unwrap();
+        assert!(options.json);
+        assert_eq!(options.day_args, vec!["--policy", "left", "--json"]);
+    }
+
+    #[test]
+    fn bench_iterations_are_optional() {
+        assert_eq!(parse(&["--bench"]).unwrap().bench, Some(DEFAULT_ITERATIONS));
+        assert_eq!(parse(&["--bench", "10"]).unwrap().bench, Some(10));
+
+        let options = parse(&["--bench", "--out", "bench.csv"]).unwrap();
+        assert_eq!(options.bench, Some(DEFAULT_ITERATIONS));
+        assert_eq!(options.out, Some(PathBuf::from("bench.csv")));
+    }
+
+    #[test]
+    fn rejects_nonsense() {
+        assert!(parse(&["--bench", "0"]).is_err());
+        assert!(parse(&["--input"]).is_err());
+        assert!(parse(&["--out", "bench.csv"]).is_err());
+        assert!(parse(&["--frobnicate"]).is_err());
+        assert!(parse(&["--answers"]).is_err());
+        assert!(parse(&["--record", "--bench"]).is_err());
+    }
+}